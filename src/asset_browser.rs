@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{scene, Notifications, Selected};
+
+// listing stops after this many files, so pointing the browser at a huge directory by mistake
+// doesn't hang the app
+const MAX_ASSET_FILES: usize = 10_000;
+
+// the image formats bevy can load, depending on which of its features are turned on
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "hdr", "ktx2", "dds", "tga", "bmp"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Scene,
+    Image,
+}
+
+impl AssetKind {
+    fn of(path: &Path) -> Option<AssetKind> {
+        if scene::is_gltf(path) {
+            return Some(AssetKind::Scene);
+        }
+        let extension = path.extension()?.to_str()?;
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|image| extension.eq_ignore_ascii_case(image))
+            .then_some(AssetKind::Image)
+    }
+}
+
+struct AssetFile {
+    // the full path, so that it loads the same wherever the asset server's own folder is
+    path: PathBuf,
+    // relative to the browsed directory, which is what's shown in the list
+    name: String,
+    // kept to filter by, rather than lowercasing every name every frame
+    lowercase_name: String,
+    kind: AssetKind,
+}
+
+// the directory the assets tab lists the meshes and textures in
+#[derive(Resource)]
+pub struct AssetBrowserSettings {
+    pub directory: PathBuf,
+    // the files found the last time the directory was listed, which is only done when the tab is
+    // first shown or refreshed
+    files: Option<Result<Vec<AssetFile>, String>>,
+}
+
+impl Default for AssetBrowserSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("assets"),
+            files: None,
+        }
+    }
+}
+
+// every mesh and texture under the directory, sorted by their paths
+fn list_assets(directory: &Path) -> Result<Vec<AssetFile>, String> {
+    let root = std::fs::canonicalize(directory).map_err(|err| err.to_string())?;
+    let mut files = Vec::new();
+    let mut directories = vec![root.clone()];
+    while let Some(directory) = directories.pop() {
+        // a subdirectory that can't be read is skipped rather than failing the whole listing
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                directories.push(path);
+                continue;
+            }
+            let Some(kind) = AssetKind::of(&path) else {
+                continue;
+            };
+            let name = path
+                .strip_prefix(&root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.push(AssetFile {
+                path,
+                lowercase_name: name.to_lowercase(),
+                name,
+                kind,
+            });
+            if files.len() >= MAX_ASSET_FILES {
+                warn!(
+                    "Only listing the first {MAX_ASSET_FILES} assets in {}",
+                    root.display()
+                );
+                directories.clear();
+                break;
+            }
+        }
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+pub fn asset_browser_ui(ui: &mut egui::Ui, world: &mut World) {
+    // the filter is just ui state, so it lives in egui's memory
+    let filter_id = ui.id().with("asset_filter");
+    let mut filter = ui
+        .data(|data| data.get_temp::<String>(filter_id))
+        .unwrap_or_default();
+    let mut refresh = false;
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.text_edit_singleline(&mut filter);
+        refresh = ui
+            .button("Refresh")
+            .on_hover_text("List the directory again, to pick up any files added since")
+            .clicked();
+    });
+    ui.data_mut(|data| data.insert_temp(filter_id, filter.clone()));
+
+    let has_files = world.resource::<AssetBrowserSettings>().files.is_some();
+    if refresh || !has_files {
+        let mut settings = world.resource_mut::<AssetBrowserSettings>();
+        let files = list_assets(&settings.directory);
+        settings.files = Some(files);
+    }
+
+    let settings = world.resource::<AssetBrowserSettings>();
+    ui.label("Double click to add to the scene, or put a texture on the selection")
+        .on_hover_text(settings.directory.display().to_string());
+    ui.separator();
+    let files = match &settings.files {
+        Some(Ok(files)) => files,
+        Some(Err(err)) => {
+            ui.label(format!(
+                "Could not list {}: {err}",
+                settings.directory.display()
+            ));
+            return;
+        }
+        None => return,
+    };
+    let filter = filter.to_lowercase();
+    let shown: Vec<&AssetFile> = files
+        .iter()
+        .filter(|file| file.lowercase_name.contains(&filter))
+        .collect();
+    if shown.is_empty() {
+        ui.label("No meshes or textures found");
+        return;
+    }
+
+    // only the rows that are scrolled into view get laid out, so long lists stay quick to draw
+    let mut activated = None;
+    let row_height = ui.spacing().interact_size.y;
+    egui::ScrollArea::vertical()
+        .auto_shrink([false; 2])
+        .show_rows(ui, row_height, shown.len(), |ui, rows| {
+            for file in &shown[rows] {
+                let kind = match file.kind {
+                    AssetKind::Scene => "Mesh",
+                    AssetKind::Image => "Texture",
+                };
+                let response = ui.selectable_label(false, format!("{}  ({kind})", file.name));
+                if response.double_clicked() {
+                    activated = Some((file.path.clone(), file.kind));
+                }
+            }
+        });
+
+    match activated {
+        Some((path, AssetKind::Scene)) => {
+            scene::load_scene(world, path);
+        }
+        Some((path, AssetKind::Image)) => {
+            let material = (**world.resource::<Selected>())
+                .and_then(|entity| world.get::<Handle<StandardMaterial>>(entity))
+                .cloned();
+            match material {
+                Some(material) => scene::load_texture(world, material, path),
+                None => world
+                    .resource_mut::<Notifications>()
+                    .warning("Select something with a material to put the texture on"),
+            }
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::event::ManualEventReader,
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_egui::egui;
+
+use crate::{scene, Primitive, SceneObject, Selected};
+
+// the console only remembers this many lines, dropping the oldest ones first
+const CONSOLE_CAPACITY: usize = 500;
+
+// runs a console command, given the words typed after its name, returning what to print
+pub type ConsoleCommand = dyn Fn(&mut World, &[&str]) -> String + Send + Sync;
+
+// a line typed into the console tab, which gets run once the ui is done for the frame
+#[derive(Event)]
+pub struct ConsoleInput(pub String);
+
+// everything the console has printed, including the commands themselves
+#[derive(Resource, Default)]
+pub struct ConsoleOutput(VecDeque<String>);
+
+impl ConsoleOutput {
+    pub fn push(&mut self, line: impl Into<String>) {
+        if self.0.len() >= CONSOLE_CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(line.into());
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+// the commands the console understands, by name
+#[derive(Resource)]
+pub struct ConsoleCommands(HashMap<String, Arc<ConsoleCommand>>);
+
+impl ConsoleCommands {
+    // registering a name again replaces the command that was already there
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        command: impl Fn(&mut World, &[&str]) -> String + Send + Sync + 'static,
+    ) {
+        self.0.insert(name.into(), Arc::new(command));
+    }
+}
+
+impl Default for ConsoleCommands {
+    fn default() -> Self {
+        let mut commands = Self(HashMap::new());
+        commands.register("help", help);
+        commands.register("spawn", spawn);
+        commands.register("select", select);
+        commands.register("delete", delete);
+        commands.register("clear", |world, _| {
+            world.resource_mut::<ConsoleOutput>().clear();
+            String::new()
+        });
+        commands.register("fps", fps);
+        commands
+    }
+}
+
+// lets apps add their own commands with `app.register_console_command("name", |world, args| ...)`
+pub trait RegisterConsoleCommand {
+    fn register_console_command(
+        &mut self,
+        name: impl Into<String>,
+        command: impl Fn(&mut World, &[&str]) -> String + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl RegisterConsoleCommand for App {
+    fn register_console_command(
+        &mut self,
+        name: impl Into<String>,
+        command: impl Fn(&mut World, &[&str]) -> String + Send + Sync + 'static,
+    ) -> &mut Self {
+        // this can be called before or after the plugin is added
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .register(name, command);
+        self
+    }
+}
+
+// run whatever was typed into the console, printing it and then what it did
+pub fn run_console_commands(world: &mut World, mut reader: Local<ManualEventReader<ConsoleInput>>) {
+    let inputs: Vec<String> = reader
+        .iter(world.resource::<Events<ConsoleInput>>())
+        .map(|input| input.0.clone())
+        .collect();
+    for input in inputs {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            continue;
+        };
+        world
+            .resource_mut::<ConsoleOutput>()
+            .push(format!("> {input}"));
+        // the command is cloned out of the registry, so that it can look at the registry itself
+        let command = world.resource::<ConsoleCommands>().0.get(name).cloned();
+        let output = match command {
+            Some(command) => command(world, args),
+            None => format!("Unknown command {name}, type help for a list of commands"),
+        };
+        // commands which just do something (or clear the console) don't need to say so
+        if !output.is_empty() {
+            world.resource_mut::<ConsoleOutput>().push(output);
+        }
+    }
+}
+
+fn help(world: &mut World, _args: &[&str]) -> String {
+    let mut names: Vec<&str> = world
+        .resource::<ConsoleCommands>()
+        .0
+        .keys()
+        .map(|name| name.as_str())
+        .collect();
+    names.sort();
+    format!("Commands: {}", names.join(", "))
+}
+
+// add a primitive to the scene and select it, like the add menu does
+fn spawn(world: &mut World, args: &[&str]) -> String {
+    let primitive = args.first().and_then(|name| {
+        Primitive::ALL
+            .into_iter()
+            .find(|primitive| primitive.name().eq_ignore_ascii_case(name))
+    });
+    let Some(primitive) = primitive else {
+        let names: Vec<&str> = Primitive::ALL
+            .iter()
+            .map(|primitive| primitive.name())
+            .collect();
+        return format!("Usage: spawn <{}>", names.join("|").to_lowercase());
+    };
+    let entity = scene::spawn_primitive(world, primitive);
+    **world.resource_mut::<Selected>() = Some(entity);
+    format!("Spawned {} {entity:?}", primitive.name())
+}
+
+// select something in the scene by its entity index (the number before the v in its id), or
+// nothing with `select none`
+fn select(world: &mut World, args: &[&str]) -> String {
+    let selection = match args.first() {
+        Some(&"none") => None,
+        Some(index) => {
+            let Ok(index) = index.parse::<u32>() else {
+                return "Usage: select <entity index|none>".to_owned();
+            };
+            let entity = world
+                .query_filtered::<Entity, With<SceneObject>>()
+                .iter(world)
+                .find(|entity| entity.index() == index);
+            let Some(entity) = entity else {
+                return format!("There's nothing in the scene with the index {index}");
+            };
+            Some(entity)
+        }
+        None => return "Usage: select <entity index|none>".to_owned(),
+    };
+    **world.resource_mut::<Selected>() = selection;
+    match selection {
+        Some(entity) => format!("Selected {entity:?}"),
+        None => "Cleared the selection".to_owned(),
+    }
+}
+
+// delete the selection, with the same rules about what can be deleted as everywhere else
+fn delete(world: &mut World, _args: &[&str]) -> String {
+    let Some(selected) = **world.resource::<Selected>() else {
+        return "Nothing is selected".to_owned();
+    };
+    if !scene::delete(world, selected) {
+        return format!("{selected:?} can't be deleted");
+    }
+    **world.resource_mut::<Selected>() = None;
+    format!("Deleted {selected:?}")
+}
+
+fn fps(world: &mut World, _args: &[&str]) -> String {
+    let diagnostics = world.resource::<DiagnosticsStore>();
+    let smoothed = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+    let frame_stats = smoothed(FrameTimeDiagnosticsPlugin::FPS)
+        .zip(smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME));
+    match frame_stats {
+        Some((fps, frame_time)) => format!("{fps:.0} fps ({frame_time:.2} ms)"),
+        None => "The frame time hasn't been measured yet".to_owned(),
+    }
+}
+
+pub fn console_ui(ui: &mut egui::Ui, world: &mut World) {
+    // what's being typed is just ui state, so it lives in egui's memory
+    let input_id = ui.id().with("console_input");
+    let mut input = ui
+        .data(|data| data.get_temp::<String>(input_id))
+        .unwrap_or_default();
+    // laid out from the bottom, so the input stays at the bottom with the output filling the rest
+    ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut input)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                .hint_text("Type help for a list of commands"),
+        );
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut input);
+            if !line.trim().is_empty() {
+                world
+                    .resource_mut::<Events<ConsoleInput>>()
+                    .send(ConsoleInput(line));
+            }
+            // keep typing without having to click back into it
+            response.request_focus();
+        }
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                // the scroll area's contents go back to being laid out from the top
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    for line in &world.resource::<ConsoleOutput>().0 {
+                        ui.label(egui::RichText::new(line).monospace());
+                    }
+                });
+            });
+    });
+    ui.data_mut(|data| data.insert_temp(input_id, input));
+}
@@ -0,0 +1,125 @@
+use bevy::{
+    asset::LoadState,
+    core_pipeline::Skybox,
+    pbr::EnvironmentMapLight,
+    prelude::*,
+    render::render_resource::{TextureViewDescriptor, TextureViewDimension},
+};
+use bevy_egui::egui;
+
+use crate::{Notifications, ViewportCamera};
+
+// the cubemap the viewports show behind the scene and light it with, if there is one. otherwise
+// they're just cleared to the background color
+#[derive(Resource, Default)]
+pub struct EnvironmentSettings {
+    // the path typed into the render tab, relative to the assets folder
+    pub path: String,
+    image: Option<Handle<Image>>,
+    // the image being loaded, which replaces the one in use once it's ready
+    loading: Option<Handle<Image>>,
+}
+
+pub fn environment_ui(ui: &mut egui::Ui, world: &mut World) {
+    // edit a copy, so the cameras aren't updated every frame just for the path being shown
+    let mut path = world.resource::<EnvironmentSettings>().path.clone();
+    let loading = world.resource::<EnvironmentSettings>().loading.is_some();
+    ui.horizontal(|ui| {
+        ui.label("Environment:");
+        ui.text_edit_singleline(&mut path)
+            .on_hover_text("A cubemap, either a ktx2/dds file or an image with its faces stacked");
+    });
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(!path.is_empty(), egui::Button::new("Load"))
+            .clicked()
+        {
+            let image = world.resource::<AssetServer>().load(path.as_str());
+            world.resource_mut::<EnvironmentSettings>().loading = Some(image);
+        }
+        if ui.button("None").clicked() {
+            let mut settings = world.resource_mut::<EnvironmentSettings>();
+            settings.image = None;
+            settings.loading = None;
+        }
+        if loading {
+            ui.spinner();
+        }
+    });
+    if path != world.resource::<EnvironmentSettings>().path {
+        world.resource_mut::<EnvironmentSettings>().path = path;
+    }
+}
+
+// start using the new environment once it's loaded, letting the user know how it went since it
+// could take a while
+pub fn check_environment_load(
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut settings: ResMut<EnvironmentSettings>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Some(handle) = settings.loading.clone() else {
+        return;
+    };
+    let is_cubemap = match asset_server.get_load_state(&handle) {
+        LoadState::Loaded => images.get_mut(&handle).is_some_and(make_cubemap),
+        LoadState::Failed => false,
+        _ => return,
+    };
+    settings.loading = None;
+    if is_cubemap {
+        settings.image = Some(handle);
+        notifications.info(format!("Loaded the environment from {}", settings.path));
+    } else {
+        warn!("Could not load a cubemap from {}", settings.path);
+        notifications.error(format!("Could not load a cubemap from {}", settings.path));
+    }
+}
+
+// ktx2 and dds files can be cubemaps already, but other images need telling that their six faces
+// are stacked on top of each other. returns false if the image can't be a cubemap
+fn make_cubemap(image: &mut Image) -> bool {
+    if image.texture_descriptor.array_layer_count() == 1 {
+        if image.height() != image.width() * 6 {
+            return false;
+        }
+        image.reinterpret_stacked_2d_as_array(6);
+    }
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    true
+}
+
+// give the viewport cameras the environment as their skybox whenever it changes, or a new viewport
+// appears. the same cubemap is used for both the diffuse and specular lighting, which isn't as
+// accurate as prefiltered maps would be but saves making them
+pub fn apply_environment(
+    mut commands: Commands,
+    settings: Res<EnvironmentSettings>,
+    cameras: Query<(Entity, Ref<ViewportCamera>)>,
+) {
+    for (entity, marker) in &cameras {
+        if !settings.is_changed() && !marker.is_added() {
+            continue;
+        }
+        match &settings.image {
+            Some(image) => {
+                commands.entity(entity).insert((
+                    Skybox(image.clone()),
+                    EnvironmentMapLight {
+                        diffuse_map: image.clone(),
+                        specular_map: image.clone(),
+                    },
+                ));
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .remove::<(Skybox, EnvironmentMapLight)>();
+            }
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::SnapSettings;
+
+// which kind of manipulation the transform gizmo does
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+    // no gizmo at all, for when it's just in the way
+    None,
+}
+
+impl GizmoMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
+            GizmoMode::None => "None",
+        }
+    }
+}
+
+// whether the gizmo's handles line up with the world's axes or with the object's own
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoSpace {
+    #[default]
+    World,
+    Local,
+}
+
+impl GizmoSpace {
+    pub fn name(self) -> &'static str {
+        match self {
+            GizmoSpace::World => "World",
+            GizmoSpace::Local => "Local",
+        }
+    }
+}
+
+// the gizmo has one handle for each axis
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+
+    fn direction(self) -> Vec3 {
+        match self {
+            Axis::X => Vec3::X,
+            Axis::Y => Vec3::Y,
+            Axis::Z => Vec3::Z,
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            Axis::X => egui::Color32::from_rgb(230, 60, 60),
+            Axis::Y => egui::Color32::from_rgb(60, 200, 60),
+            Axis::Z => egui::Color32::from_rgb(60, 110, 240),
+        }
+    }
+}
+
+// a handle being dragged. the transform is worked out from how it was when the drag started each
+// frame, rather than nudged along by each frame's movement, so that snapping doesn't round every
+// small movement away
+#[derive(Clone, Copy)]
+struct Drag {
+    axis: Axis,
+    start: Transform,
+    // how far the handle has been dragged in total: a distance in world units when translating, an
+    // angle in radians when rotating, and a factor when scaling
+    total: f32,
+}
+
+// how long the handles are on screen in points, no matter how far away the target is
+const HANDLE_LENGTH: f32 = 80.;
+// how close the pointer has to be to a handle (in points) to grab it
+const GRAB_DISTANCE: f32 = 8.;
+// how many line segments the rotation rings are drawn with
+const RING_SEGMENTS: usize = 48;
+// stop the scale handles from collapsing an object down to nothing
+const MIN_SCALE: f32 = 0.01;
+const HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(250, 220, 60);
+
+// a translate/rotate/scale gizmo drawn over the viewport image. it takes its input from the
+// viewport image's own response, so that it can decide whether a drag should move the object or be
+// left for the camera controls
+pub struct Gizmo {
+    // used to remember which handle is being dragged between frames
+    pub id: egui::Id,
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    // what to snap to, if snapping
+    pub snap: Option<SnapSettings>,
+    // the camera that the viewport is rendered from
+    pub view_projection: Mat4,
+    pub camera_transform: GlobalTransform,
+    // where the viewport image is on screen
+    pub rect: egui::Rect,
+}
+
+impl Gizmo {
+    // draw the gizmo and apply any dragging of its handles to the transform.
+    // returns true while a handle is being dragged, so the drag shouldn't also be used for anything else
+    pub fn interact(
+        &self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        transform: &mut Transform,
+    ) -> bool {
+        if self.mode == GizmoMode::None {
+            return false;
+        }
+        let Some(center) = self.world_to_screen(transform.translation) else {
+            return false;
+        };
+        let length = self.world_length(transform.translation);
+        let pointer = ui.input(|input| input.pointer.interact_pos());
+
+        let mut active = ui.data(|data| data.get_temp::<Drag>(self.id));
+        let dragging = response.dragged_by(egui::PointerButton::Primary);
+        if response.drag_started() && dragging {
+            // only grab a handle if the drag started on one, otherwise leave it alone
+            active = pointer
+                .and_then(|pointer| self.pick(pointer, transform, center, length))
+                .map(|axis| Drag {
+                    axis,
+                    start: *transform,
+                    total: if self.mode == GizmoMode::Scale {
+                        1.
+                    } else {
+                        0.
+                    },
+                });
+        }
+        if !dragging {
+            active = None;
+        }
+
+        if let Some(drag) = &mut active {
+            self.drag(
+                drag,
+                response.drag_delta(),
+                pointer,
+                transform,
+                center,
+                length,
+            );
+        }
+        ui.data_mut(|data| match active {
+            Some(drag) => {
+                data.insert_temp(self.id, drag);
+            }
+            None => {
+                data.remove::<Drag>(self.id);
+            }
+        });
+
+        // the transform may well have moved, so work out where to draw from again
+        if let Some(center) = self.world_to_screen(transform.translation) {
+            let length = self.world_length(transform.translation);
+            let highlighted = active.map(|drag| drag.axis).or_else(|| {
+                response
+                    .hover_pos()
+                    .and_then(|pointer| self.pick(pointer, transform, center, length))
+            });
+            self.draw(ui, transform, center, length, highlighted);
+        }
+
+        active.is_some()
+    }
+
+    // which handle (if any) is under the pointer
+    fn pick(
+        &self,
+        pointer: egui::Pos2,
+        transform: &Transform,
+        center: egui::Pos2,
+        length: f32,
+    ) -> Option<Axis> {
+        Axis::ALL
+            .into_iter()
+            .filter_map(|axis| {
+                let direction = self.axis_direction(axis, transform);
+                let distance = match self.mode {
+                    GizmoMode::Rotate => self
+                        .ring_points(transform.translation, direction, length)
+                        .windows(2)
+                        .map(|segment| distance_to_segment(pointer, segment[0], segment[1]))
+                        .fold(f32::INFINITY, f32::min),
+                    GizmoMode::Translate | GizmoMode::Scale => {
+                        let end =
+                            self.world_to_screen(transform.translation + direction * length)?;
+                        distance_to_segment(pointer, center, end)
+                    }
+                    GizmoMode::None => return None,
+                };
+                (distance <= GRAB_DISTANCE).then_some((axis, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(axis, _)| axis)
+    }
+
+    fn drag(
+        &self,
+        drag: &mut Drag,
+        delta: egui::Vec2,
+        pointer: Option<egui::Pos2>,
+        transform: &mut Transform,
+        center: egui::Pos2,
+        length: f32,
+    ) {
+        let Drag { axis, start, .. } = *drag;
+        let direction = self.axis_direction(axis, &start);
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                let Some(end) = self.world_to_screen(transform.translation + direction * length)
+                else {
+                    return;
+                };
+                let screen_axis = to_vec2(end - center);
+                // the axis is pointing straight at the camera, so there's no sensible way to drag it
+                if screen_axis.length_squared() < 1. {
+                    return;
+                }
+                // how far along the handle the pointer moved, as a fraction of the handle's length
+                let amount = to_vec2(delta).dot(screen_axis) / screen_axis.length_squared();
+                if self.mode == GizmoMode::Translate {
+                    drag.total += length * amount;
+                    let mut translation = start.translation;
+                    match (self.snap, self.space) {
+                        // moving along a world axis snaps to the grid itself
+                        (Some(snap), GizmoSpace::World) => {
+                            translation += direction * drag.total;
+                            let moved = &mut translation[axis.index()];
+                            *moved = snap_to(*moved, snap.translate);
+                        }
+                        // the object's own axes don't line up with the grid, so snap how far it's
+                        // moved instead
+                        (Some(snap), GizmoSpace::Local) => {
+                            translation += direction * snap_to(drag.total, snap.translate);
+                        }
+                        (None, _) => translation += direction * drag.total,
+                    }
+                    transform.translation = translation;
+                } else {
+                    drag.total = (drag.total * (1. + amount)).max(MIN_SCALE);
+                    let factor = match self.snap {
+                        Some(snap) => snap_to(drag.total, snap.scale).max(snap.scale),
+                        None => drag.total,
+                    };
+                    transform.scale[axis.index()] =
+                        (start.scale[axis.index()] * factor).max(MIN_SCALE);
+                }
+            }
+            GizmoMode::Rotate => {
+                let Some(pointer) = pointer else {
+                    return;
+                };
+                let from = to_vec2(pointer - delta - center);
+                let to = to_vec2(pointer - center);
+                if from.length_squared() < 1. || to.length_squared() < 1. {
+                    return;
+                }
+                // screen space has y pointing down, so a positive angle here is clockwise on screen,
+                // which is a negative rotation around an axis pointing towards the camera
+                let angle = from.angle_between(to);
+                let towards_camera = direction.dot(self.camera_transform.back()) > 0.;
+                drag.total += if towards_camera { -angle } else { angle };
+                let angle = match self.snap {
+                    Some(snap) => snap_to(drag.total, snap.rotate.to_radians()),
+                    None => drag.total,
+                };
+                transform.rotation = Quat::from_axis_angle(direction, angle) * start.rotation;
+            }
+            GizmoMode::None => {}
+        }
+    }
+
+    fn draw(
+        &self,
+        ui: &egui::Ui,
+        transform: &Transform,
+        center: egui::Pos2,
+        length: f32,
+        highlighted: Option<Axis>,
+    ) {
+        let painter = ui.painter_at(self.rect);
+        for axis in Axis::ALL {
+            let color = if highlighted == Some(axis) {
+                HIGHLIGHT_COLOR
+            } else {
+                axis.color()
+            };
+            let stroke = egui::Stroke::new(3., color);
+            let direction = self.axis_direction(axis, transform);
+            match self.mode {
+                GizmoMode::Rotate => {
+                    let points = self.ring_points(transform.translation, direction, length);
+                    painter.add(egui::Shape::line(points, stroke));
+                }
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let Some(end) =
+                        self.world_to_screen(transform.translation + direction * length)
+                    else {
+                        continue;
+                    };
+                    painter.line_segment([center, end], stroke);
+                    if self.mode == GizmoMode::Translate {
+                        painter.circle_filled(end, 5., color);
+                    } else {
+                        let rect = egui::Rect::from_center_size(end, egui::vec2(9., 9.));
+                        painter.rect_filled(rect, 0., color);
+                    }
+                }
+                GizmoMode::None => {}
+            }
+        }
+    }
+
+    // scaling always happens along the object's own axes, since that's what its scale is relative
+    // to. moving and rotating use whichever space has been picked
+    fn axis_direction(&self, axis: Axis, transform: &Transform) -> Vec3 {
+        match (self.mode, self.space) {
+            (GizmoMode::Scale, _) | (_, GizmoSpace::Local) => transform.rotation * axis.direction(),
+            (GizmoMode::Translate | GizmoMode::Rotate | GizmoMode::None, GizmoSpace::World) => {
+                axis.direction()
+            }
+        }
+    }
+
+    // the points making up the rotation ring around the given axis, in screen space
+    fn ring_points(&self, center: Vec3, axis: Vec3, radius: f32) -> Vec<egui::Pos2> {
+        let rotation = Quat::from_rotation_arc(Vec3::Z, axis);
+        (0..=RING_SEGMENTS)
+            .filter_map(|i| {
+                let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                let offset = rotation * Vec3::new(angle.cos(), angle.sin(), 0.) * radius;
+                self.world_to_screen(center + offset)
+            })
+            .collect()
+    }
+
+    // the length in world units which appears on screen as HANDLE_LENGTH at the given position
+    fn world_length(&self, position: Vec3) -> f32 {
+        let up = self.camera_transform.up();
+        match (
+            self.world_to_screen(position),
+            self.world_to_screen(position + up),
+        ) {
+            (Some(a), Some(b)) if a.distance(b) > f32::EPSILON => HANDLE_LENGTH / a.distance(b),
+            _ => 1.,
+        }
+    }
+
+    // project a point in the world onto the viewport image, or None if it's behind the camera
+    fn world_to_screen(&self, position: Vec3) -> Option<egui::Pos2> {
+        let clip = self.view_projection * position.extend(1.);
+        if clip.w <= 0. {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        Some(egui::pos2(
+            self.rect.left() + (ndc.x + 1.) / 2. * self.rect.width(),
+            self.rect.top() + (1. - ndc.y) / 2. * self.rect.height(),
+        ))
+    }
+}
+
+// round to the nearest multiple of the step, leaving the value alone if there's no step to snap to
+fn snap_to(value: f32, step: f32) -> f32 {
+    if step > 0. {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+fn to_vec2(vec: egui::Vec2) -> Vec2 {
+    Vec2::new(vec.x, vec.y)
+}
+
+fn distance_to_segment(point: egui::Pos2, start: egui::Pos2, end: egui::Pos2) -> f32 {
+    let (point, start, end) = (
+        to_vec2(point.to_vec2()),
+        to_vec2(start.to_vec2()),
+        to_vec2(end.to_vec2()),
+    );
+    let segment = end - start;
+    let t = if segment.length_squared() > 0. {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0., 1.)
+    } else {
+        0.
+    };
+    point.distance(start + segment * t)
+}
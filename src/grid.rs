@@ -0,0 +1,122 @@
+use bevy::{prelude::*, render::render_resource::PrimitiveTopology};
+
+// how many lines the grid has in each direction. it's finite, but big enough that the edges are
+// rarely seen
+const GRID_LINES: u32 = 100;
+
+// marker struct for the ground grid
+#[derive(Component)]
+pub struct GridMarker;
+
+// how the ground grid looks
+#[derive(Resource)]
+pub struct GridSettings {
+    pub visible: bool,
+    // the distance between neighbouring lines, in world units
+    pub spacing: f32,
+    pub color: Color,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            spacing: 1.0,
+            color: Color::rgba(0.8, 0.8, 0.8, 0.5),
+        }
+    }
+}
+
+// what the transform gizmo snaps to while snapping is enabled (or ctrl is held)
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    // in world units
+    pub translate: f32,
+    // in degrees
+    pub rotate: f32,
+    // as a fraction of the original scale
+    pub scale: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            translate: 1.0,
+            rotate: 15.0,
+            scale: 0.1,
+        }
+    }
+}
+
+pub fn setup_grid(
+    mut commands: Commands,
+    settings: Res<GridSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(grid_mesh(settings.spacing)),
+            material: materials.add(grid_material(settings.color)),
+            ..default()
+        },
+        GridMarker,
+        Name::new("Grid"),
+    ));
+}
+
+// rebuild the grid whenever its settings are changed from the render tab
+pub fn update_grid(
+    settings: Res<GridSettings>,
+    mut grid: Query<(&mut Visibility, &Handle<Mesh>, &Handle<StandardMaterial>), With<GridMarker>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut visibility, mesh, material) in &mut grid {
+        *visibility = if settings.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            *mesh = grid_mesh(settings.spacing);
+        }
+        if let Some(material) = materials.get_mut(material) {
+            *material = grid_material(settings.color);
+        }
+    }
+}
+
+// a square of lines on the ground, centred on the origin
+fn grid_mesh(spacing: f32) -> Mesh {
+    let extent = spacing * GRID_LINES as f32 / 2.;
+    let mut positions = Vec::new();
+    for i in 0..=GRID_LINES {
+        let offset = i as f32 * spacing - extent;
+        positions.push([offset, 0., -extent]);
+        positions.push([offset, 0., extent]);
+        positions.push([-extent, 0., offset]);
+        positions.push([extent, 0., offset]);
+    }
+    let normals = vec![[0., 1., 0.]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh
+}
+
+fn grid_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        // the lines shouldn't be shaded by the scene's lights
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    }
+}
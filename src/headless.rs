@@ -0,0 +1,79 @@
+use bevy::{prelude::*, window::ExitCondition, winit::WinitPlugin};
+
+use crate::{
+    screenshot::{LastScreenshot, ScreenshotRequests, ScreenshotSettings},
+    DockingViewportPlugin, FrameScene, ViewportId, Viewports,
+};
+
+// how many more frames to wait for the gpu to hand the image back before giving up
+const MAX_READBACK_FRAMES: u32 = 100;
+
+// how many frames are left to render before the viewport gets copied back
+#[derive(Resource, Deref, DerefMut)]
+struct FramesLeft(u32);
+
+// run the editor without a window for the given number of frames, then copy the first viewport's
+// image back from the gpu and return it (it also gets saved, like any other screenshot). the
+// build function adds whatever the scene needs, like its startup systems. nothing moves the
+// cameras in the meantime, so the same scene always renders the same image
+pub fn render_frames(frames: u32, build: impl FnOnce(&mut App)) -> Option<Image> {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            // without a window there's nothing for winit to do, so the frames are run by hand below
+            .disable::<WinitPlugin>(),
+    )
+    // always start from the default layout and settings, whatever was saved in the working
+    // directory, so that it's only the scene that decides what gets rendered
+    .add_plugins(DockingViewportPlugin::default().without_persistence())
+    // leave the cameras where they start, rather than framing the scene
+    .insert_resource(FrameScene(false))
+    // the viewport needs a couple of frames to be set up and drawn before there's anything to copy
+    .insert_resource(FramesLeft(frames.max(2)))
+    .add_systems(Update, request_readback);
+    // the runner stops as soon as the viewport's image is back, so there's no time to save it in
+    // the background
+    app.world.resource_mut::<ScreenshotSettings>().wait_for_save = true;
+    build(&mut app);
+
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    app.set_runner(move |mut app| {
+        for _ in 0..frames.max(2) + MAX_READBACK_FRAMES {
+            app.update();
+            if let Some(image) = app.world.resource_mut::<LastScreenshot>().take() {
+                let _ = sender.send(image);
+                return;
+            }
+        }
+        warn!("Gave up waiting for the viewport image to be read back from the gpu");
+    });
+    app.run();
+    receiver.try_recv().ok()
+}
+
+// ask for the first viewport's image to be copied back once the frames have all been rendered
+fn request_readback(
+    mut frames_left: ResMut<FramesLeft>,
+    viewports: Res<Viewports>,
+    mut requests: ResMut<ScreenshotRequests>,
+) {
+    if **frames_left == 0 {
+        return;
+    }
+    **frames_left -= 1;
+    if **frames_left > 0 {
+        return;
+    }
+    let viewport = viewports
+        .get(&ViewportId(0))
+        .or_else(|| viewports.values().next());
+    match viewport {
+        Some(viewport) => requests.push(viewport.image.clone()),
+        None => warn!("There's no viewport to render, is one open in the layout?"),
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::Selected;
+
+// undoing only goes back this many edits, dropping the oldest ones first
+const MAX_HISTORY: usize = 100;
+
+// a change made from the editor which can be undone and redone. each one keeps what it changed
+// from and to, so that it can be applied in either direction
+pub enum EditAction {
+    Transform {
+        entity: Entity,
+        before: Transform,
+        after: Transform,
+    },
+    MaterialColor {
+        material: Handle<StandardMaterial>,
+        before: Color,
+        after: Color,
+    },
+}
+
+impl EditAction {
+    fn apply(&self, world: &mut World, undo: bool) {
+        match self {
+            EditAction::Transform {
+                entity,
+                before,
+                after,
+            } => {
+                // the entity might have been deleted since
+                if let Some(mut transform) = world.get_mut::<Transform>(*entity) {
+                    *transform = if undo { *before } else { *after };
+                }
+            }
+            EditAction::MaterialColor {
+                material,
+                before,
+                after,
+            } => {
+                let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+                if let Some(material) = materials.get_mut(material) {
+                    material.base_color = if undo { *before } else { *after };
+                }
+            }
+        }
+    }
+}
+
+// the selected entity's editable state, from before the edit that's in progress
+struct Snapshot {
+    entity: Entity,
+    transform: Option<Transform>,
+    color: Option<(Handle<StandardMaterial>, Color)>,
+}
+
+// the edits that can be undone, and the undone ones that can be redone
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: VecDeque<EditAction>,
+    redo: Vec<EditAction>,
+    baseline: Option<Snapshot>,
+    // set by the ui whenever it edits the selection, so that changes made by anything else (like
+    // the app animating it) aren't recorded
+    edited: bool,
+}
+
+impl EditHistory {
+    pub fn push(&mut self, action: EditAction) {
+        self.push_undo(action);
+        // a new edit takes the history off in another direction
+        self.redo.clear();
+    }
+
+    // redoing goes through this too, so the history never grows past its cap
+    fn push_undo(&mut self, action: EditAction) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(action);
+    }
+
+    pub fn mark_edited(&mut self) {
+        self.edited = true;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+pub fn undo(world: &mut World) {
+    let mut history = world.resource_mut::<EditHistory>();
+    let Some(action) = history.undo.pop_back() else {
+        return;
+    };
+    // the change shouldn't be recorded as an edit of its own
+    history.baseline = None;
+    action.apply(world, true);
+    world.resource_mut::<EditHistory>().redo.push(action);
+}
+
+pub fn redo(world: &mut World) {
+    let mut history = world.resource_mut::<EditHistory>();
+    let Some(action) = history.redo.pop() else {
+        return;
+    };
+    history.baseline = None;
+    action.apply(world, false);
+    world.resource_mut::<EditHistory>().push_undo(action);
+}
+
+// compare the selection to how it was before the ui started editing it, and record what changed.
+// this waits until the mouse is released, so that dragging a value (or the gizmo) becomes a single
+// edit rather than one for every frame of the drag
+pub fn record_edits(
+    mut history: ResMut<EditHistory>,
+    selected: Res<Selected>,
+    mouse: Res<Input<MouseButton>>,
+    entities: Query<(Option<&Transform>, Option<&Handle<StandardMaterial>>)>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    if mouse.get_pressed().next().is_some() {
+        return;
+    }
+    let current = selected.and_then(|entity| {
+        let (transform, material) = entities.get(entity).ok()?;
+        Some(Snapshot {
+            entity,
+            transform: transform.copied(),
+            color: material
+                .and_then(|material| Some((material.clone(), materials.get(material)?.base_color))),
+        })
+    });
+    let baseline = history.baseline.take();
+    let edited = std::mem::take(&mut history.edited);
+    if let (true, Some(before), Some(after)) = (edited, &baseline, &current) {
+        if before.entity == after.entity {
+            record_changes(&mut history, before, after);
+        }
+    }
+    history.baseline = current;
+}
+
+fn record_changes(history: &mut EditHistory, before: &Snapshot, after: &Snapshot) {
+    if let (Some(before_transform), Some(after_transform)) = (before.transform, after.transform) {
+        if before_transform != after_transform {
+            history.push(EditAction::Transform {
+                entity: after.entity,
+                before: before_transform,
+                after: after_transform,
+            });
+        }
+    }
+    if let (Some((before_material, before_color)), Some((after_material, after_color))) =
+        (&before.color, &after.color)
+    {
+        if before_material == after_material && before_color != after_color {
+            history.push(EditAction::MaterialColor {
+                material: after_material.clone(),
+                before: *before_color,
+                after: *after_color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_a_color_edit() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .add_asset::<StandardMaterial>()
+            .init_resource::<EditHistory>();
+        let world = &mut app.world;
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::RED.into());
+        let base_color = |world: &World| {
+            world
+                .resource::<Assets<StandardMaterial>>()
+                .get(&material)
+                .unwrap()
+                .base_color
+        };
+
+        world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .get_mut(&material)
+            .unwrap()
+            .base_color = Color::BLUE;
+        world
+            .resource_mut::<EditHistory>()
+            .push(EditAction::MaterialColor {
+                material: material.clone(),
+                before: Color::RED,
+                after: Color::BLUE,
+            });
+
+        undo(world);
+        assert_eq!(base_color(world), Color::RED);
+        assert!(!world.resource::<EditHistory>().can_undo());
+        redo(world);
+        assert_eq!(base_color(world), Color::BLUE);
+        assert!(!world.resource::<EditHistory>().can_redo());
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut history = EditHistory::default();
+        let entity = Entity::from_raw(0);
+        for i in 0..MAX_HISTORY + 10 {
+            history.push(EditAction::Transform {
+                entity,
+                before: Transform::from_xyz(i as f32, 0., 0.),
+                after: Transform::from_xyz(i as f32 + 1., 0., 0.),
+            });
+        }
+        assert_eq!(history.undo.len(), MAX_HISTORY);
+    }
+}
@@ -0,0 +1,351 @@
+use bevy::{prelude::*, reflect::ReflectMut, render::render_resource::Face};
+use bevy_egui::egui;
+
+use crate::{
+    scene::{self, TextureDropTarget},
+    EditHistory, Primitive,
+};
+
+// draw every reflectable component on the entity, plus its material if it has one, so that they
+// can all be edited
+pub fn entity_ui(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    let Some(entity_ref) = world.get_entity(entity) else {
+        ui.label("The selected entity no longer exists");
+        return;
+    };
+
+    // work out which of the entity's components have been registered for reflection
+    let mut components: Vec<_> = entity_ref
+        .archetype()
+        .components()
+        .filter_map(|component_id| {
+            let type_id = world.components().get_info(component_id)?.type_id()?;
+            let registration = type_registry.get(type_id)?;
+            let reflect_component = registration.data::<ReflectComponent>()?.clone();
+            Some((registration.short_name().to_owned(), reflect_component))
+        })
+        .collect();
+    components.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    primitive_ui(ui, world, entity);
+
+    for (name, reflect_component) in components {
+        egui::CollapsingHeader::new(&name)
+            .default_open(name == "Transform")
+            .show(ui, |ui| {
+                let mut entity_mut = world.entity_mut(entity);
+                let mut changed = false;
+                if let Some(mut component) = reflect_component.reflect_mut(&mut entity_mut) {
+                    // only flag the component as changed if it actually was, otherwise everything
+                    // that reacts to changes (e.g. transform propagation) would run every frame
+                    if reflect_ui(ui, component.bypass_change_detection()) {
+                        component.set_changed();
+                        changed = true;
+                    }
+                }
+                if changed {
+                    world.resource_mut::<EditHistory>().mark_edited();
+                }
+            });
+    }
+
+    // materials are assets rather than components, so they need fetching separately
+    let Some(handle) = world.get::<Handle<StandardMaterial>>(entity).cloned() else {
+        return;
+    };
+    egui::CollapsingHeader::new("StandardMaterial")
+        .default_open(true)
+        .show(ui, |ui| {
+            let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+            // edit a copy, since getting the material mutably marks it as modified and makes it get
+            // re-uploaded to the gpu
+            let Some(mut material) = materials.get(&handle).cloned() else {
+                return;
+            };
+            let mut changed = reflect_ui(ui, &mut material);
+            changed |= double_sided_ui(ui, &mut material);
+            if changed {
+                if let Some(original) = materials.get_mut(&handle) {
+                    *original = material;
+                }
+                world.resource_mut::<EditHistory>().mark_edited();
+            }
+            ui.separator();
+            texture_ui(ui, world, &handle);
+        });
+}
+
+// double_sided only changes how the back faces are lit, so culling is turned off (or back on) along
+// with it for them to actually be drawn
+fn double_sided_ui(ui: &mut egui::Ui, material: &mut StandardMaterial) -> bool {
+    let mut double_sided = material.double_sided && material.cull_mode.is_none();
+    let changed = ui
+        .checkbox(&mut double_sided, "Double Sided")
+        .on_hover_text(
+            "Draw the back faces too, lit as if they faced the other way. This stops thin or open \
+             meshes (or ones with their faces wound the wrong way) looking see-through from behind",
+        )
+        .changed();
+    if changed {
+        material.double_sided = double_sided;
+        material.cull_mode = if double_sided { None } else { Some(Face::Back) };
+    }
+    changed
+}
+
+// swap the mesh of one of the built in primitives for another
+fn primitive_ui(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let Some(&primitive) = world.get::<Primitive>(entity) else {
+        return;
+    };
+    let mut chosen = primitive;
+    egui::ComboBox::from_label("Mesh")
+        .selected_text(primitive.name())
+        .show_ui(ui, |ui| {
+            for option in Primitive::ALL {
+                ui.selectable_value(&mut chosen, option, option.name());
+            }
+        });
+    if chosen != primitive {
+        scene::set_primitive(world, entity, chosen);
+    }
+}
+
+// the material's albedo texture, which is loaded from either the path typed in here or an image
+// dropped onto this row
+fn texture_ui(ui: &mut egui::Ui, world: &mut World, material: &Handle<StandardMaterial>) {
+    let path_id = ui.id().with("texture_path");
+    let mut path = ui
+        .data(|data| data.get_temp::<String>(path_id))
+        .unwrap_or_default();
+    let has_texture = world
+        .resource::<Assets<StandardMaterial>>()
+        .get(material)
+        .is_some_and(|material| material.base_color_texture.is_some());
+    let response = ui
+        .horizontal(|ui| {
+            ui.label("Texture:");
+            ui.text_edit_singleline(&mut path)
+                .on_hover_text("An image in the assets folder, or drop an image file here");
+            if ui
+                .add_enabled(!path.is_empty(), egui::Button::new("Load"))
+                .clicked()
+            {
+                scene::load_texture(world, material.clone(), path.clone().into());
+            }
+            if ui
+                .add_enabled(has_texture, egui::Button::new("Clear Texture"))
+                .clicked()
+            {
+                let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+                if let Some(material) = materials.get_mut(material) {
+                    material.base_color_texture = None;
+                }
+            }
+        })
+        .response;
+    **world.resource_mut::<TextureDropTarget>() = Some((response.rect, material.clone()));
+    ui.data_mut(|data| data.insert_temp(path_id, path));
+}
+
+// numeric fields for just the entity's transform. the euler angles are remembered between frames
+// rather than worked out from the rotation every time, so that going back and forth through the
+// quaternion doesn't make them drift (or flip to an equivalent set of angles) while being dragged
+pub fn transform_ui(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let Some(mut transform) = world.get_mut::<Transform>(entity) else {
+        ui.label("The selected entity has no transform");
+        return;
+    };
+    // edit a copy, so the transform is only flagged as changed when it actually is
+    let mut edited = *transform;
+    let angles_id = ui.id().with(("euler_angles", entity));
+    let mut angles = ui
+        .data(|data| data.get_temp::<(Quat, Vec3)>(angles_id))
+        // unless the rotation has been changed from somewhere else since
+        .filter(|(rotation, _)| *rotation == transform.rotation)
+        .map_or_else(
+            || to_euler_degrees(transform.rotation),
+            |(_, angles)| angles,
+        );
+
+    egui::Grid::new("transform").num_columns(2).show(ui, |ui| {
+        ui.label("Translation");
+        vec3_ui(ui, &mut edited.translation, 0.01);
+        ui.end_row();
+        ui.label("Rotation");
+        if vec3_ui(ui, &mut angles, 1.) {
+            edited.rotation = from_euler_degrees(angles);
+        }
+        ui.end_row();
+        ui.label("Scale");
+        vec3_ui(ui, &mut edited.scale, 0.01);
+        ui.end_row();
+    });
+    if ui.button("Reset Transform").clicked() {
+        edited = Transform::IDENTITY;
+        angles = Vec3::ZERO;
+    }
+
+    let changed = edited != *transform;
+    if changed {
+        *transform = edited;
+    }
+    ui.data_mut(|data| data.insert_temp(angles_id, (edited.rotation, angles)));
+    if changed {
+        world.resource_mut::<EditHistory>().mark_edited();
+    }
+}
+
+// draw an editor for any reflected value, returning whether it was changed
+pub fn reflect_ui(ui: &mut egui::Ui, value: &mut dyn Reflect) -> bool {
+    // some types get their own widgets, rather than showing all their fields
+    if let Some(value) = value.downcast_mut::<f32>() {
+        return ui.add(egui::DragValue::new(value).speed(0.01)).changed();
+    }
+    if let Some(value) = value.downcast_mut::<f64>() {
+        return ui.add(egui::DragValue::new(value).speed(0.01)).changed();
+    }
+    if let Some(value) = value.downcast_mut::<u32>() {
+        return ui.add(egui::DragValue::new(value)).changed();
+    }
+    if let Some(value) = value.downcast_mut::<i32>() {
+        return ui.add(egui::DragValue::new(value)).changed();
+    }
+    if let Some(value) = value.downcast_mut::<usize>() {
+        return ui.add(egui::DragValue::new(value)).changed();
+    }
+    if let Some(value) = value.downcast_mut::<bool>() {
+        return ui.checkbox(value, "").changed();
+    }
+    if let Some(value) = value.downcast_mut::<String>() {
+        return ui.text_edit_singleline(value).changed();
+    }
+    if let Some(value) = value.downcast_mut::<Vec3>() {
+        return vec3_ui(ui, value, 0.01);
+    }
+    if let Some(value) = value.downcast_mut::<Color>() {
+        let mut color = value.as_rgba_f32();
+        let changed = ui.color_edit_button_rgba_unmultiplied(&mut color).changed();
+        if changed {
+            *value = color.into();
+        }
+        return changed;
+    }
+    if let Some(value) = value.downcast_mut::<Quat>() {
+        // quaternions are meaningless to edit by hand, so show them as euler angles in degrees
+        let mut angles = to_euler_degrees(*value);
+        let changed = vec3_ui(ui, &mut angles, 1.);
+        if changed {
+            *value = from_euler_degrees(angles);
+        }
+        return changed;
+    }
+
+    // otherwise, show whatever the value is made up of
+    let mut changed = false;
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            for i in 0..value.field_len() {
+                let name = value.name_at(i).unwrap_or_default().to_owned();
+                if let Some(field) = value.field_at_mut(i) {
+                    changed |= field_ui(ui, i, &name, field);
+                }
+            }
+        }
+        ReflectMut::TupleStruct(value) => {
+            for i in 0..value.field_len() {
+                if let Some(field) = value.field_mut(i) {
+                    changed |= field_ui(ui, i, &i.to_string(), field);
+                }
+            }
+        }
+        ReflectMut::Tuple(value) => {
+            for i in 0..value.field_len() {
+                if let Some(field) = value.field_mut(i) {
+                    changed |= field_ui(ui, i, &i.to_string(), field);
+                }
+            }
+        }
+        ReflectMut::List(value) => {
+            for i in 0..value.len() {
+                if let Some(item) = value.get_mut(i) {
+                    changed |= field_ui(ui, i, &i.to_string(), item);
+                }
+            }
+        }
+        ReflectMut::Array(value) => {
+            for i in 0..value.len() {
+                if let Some(item) = value.get_mut(i) {
+                    changed |= field_ui(ui, i, &i.to_string(), item);
+                }
+            }
+        }
+        // switching variant would need a default value for each one, so only the fields of the
+        // current variant can be edited
+        ReflectMut::Enum(value) => {
+            ui.label(value.variant_name());
+            for i in 0..value.field_len() {
+                let name = value
+                    .name_at(i)
+                    .map_or_else(|| i.to_string(), |name| name.to_owned());
+                if let Some(field) = value.field_at_mut(i) {
+                    changed |= field_ui(ui, i, &name, field);
+                }
+            }
+        }
+        ReflectMut::Map(value) => {
+            ui.label(format!("{:?}", value.as_reflect()));
+        }
+        ReflectMut::Value(value) => {
+            ui.label(format!("{value:?}"));
+        }
+    }
+    changed
+}
+
+// a labelled row for one field of a struct/list/etc
+fn field_ui(ui: &mut egui::Ui, index: usize, name: &str, value: &mut dyn Reflect) -> bool {
+    // each field needs its own id, otherwise nested widgets would clash with each other
+    ui.push_id(index, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(name);
+            ui.vertical(|ui| reflect_ui(ui, value)).inner
+        })
+        .inner
+    })
+    .inner
+}
+
+fn to_euler_degrees(rotation: Quat) -> Vec3 {
+    let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+    Vec3::new(x, y, z) * 180. / std::f32::consts::PI
+}
+
+fn from_euler_degrees(angles: Vec3) -> Quat {
+    let angles = angles * std::f32::consts::PI / 180.;
+    Quat::from_euler(EulerRot::XYZ, angles.x, angles.y, angles.z)
+}
+
+fn vec3_ui(ui: &mut egui::Ui, value: &mut Vec3, speed: f32) -> bool {
+    ui.horizontal(|ui| {
+        let x = ui.add(
+            egui::DragValue::new(&mut value.x)
+                .speed(speed)
+                .prefix("x: "),
+        );
+        let y = ui.add(
+            egui::DragValue::new(&mut value.y)
+                .speed(speed)
+                .prefix("y: "),
+        );
+        let z = ui.add(
+            egui::DragValue::new(&mut value.z)
+                .speed(speed)
+                .prefix("z: "),
+        );
+        x.changed() || y.changed() || z.changed()
+    })
+    .inner
+}
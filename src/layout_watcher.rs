@@ -0,0 +1,84 @@
+use std::{ffi::OsStr, path::Path};
+
+use bevy::prelude::*;
+use crossbeam_channel::Receiver;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{load_layout, repair_layout, DockTree, Notifications, TabRegistry, LAYOUT_PATH};
+
+// editors often write a file in a few goes (or delete it and write a new one), so the layout is
+// only reloaded once it's been left alone for this long, in seconds
+const RELOAD_DELAY: f64 = 0.25;
+
+// watches the layout file so that editing it outside the app shows up straight away
+#[derive(Resource)]
+pub struct LayoutWatcher {
+    // the watcher stops when it's dropped, so it needs keeping around
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+    // when the file was last changed, if it hasn't been reloaded since
+    changed_at: Option<f64>,
+}
+
+pub fn watch_layout(mut commands: Commands) {
+    let (sender, changes) = crossbeam_channel::unbounded();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        let is_layout = event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == Some(OsStr::new(LAYOUT_PATH)));
+        if is_layout && !event.kind.is_access() {
+            // the app might be exiting, in which case nothing's listening any more
+            let _ = sender.send(());
+        }
+    });
+    // the folder is watched rather than the file itself, since the file might not exist yet, and
+    // editors that replace the file rather than writing to it would stop a watch on it working
+    let watcher = watcher.and_then(|mut watcher| {
+        watcher.watch(Path::new("."), RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => commands.insert_resource(LayoutWatcher {
+            _watcher: watcher,
+            changes,
+            changed_at: None,
+        }),
+        Err(err) => warn!("Could not watch {LAYOUT_PATH} for changes: {err}"),
+    }
+}
+
+// swap in the layout from the file once it's stopped changing, as long as it's still a layout
+pub fn reload_layout(
+    watcher: Option<ResMut<LayoutWatcher>>,
+    time: Res<Time>,
+    tab_registry: Res<TabRegistry>,
+    mut docktree: ResMut<DockTree>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Some(mut watcher) = watcher else {
+        return;
+    };
+    let now = time.elapsed_seconds_f64();
+    if watcher.changes.try_iter().count() > 0 {
+        watcher.changed_at = Some(now);
+    }
+    if !watcher
+        .changed_at
+        .is_some_and(|changed_at| now - changed_at >= RELOAD_DELAY)
+    {
+        return;
+    }
+    watcher.changed_at = None;
+    // load_layout has already said what was wrong with it if it couldn't be loaded
+    match load_layout().and_then(|tree| repair_layout(tree, &tab_registry)) {
+        Some(tree) => {
+            docktree.0 = tree;
+            notifications.info(format!("Reloaded the layout from {LAYOUT_PATH}"));
+        }
+        None => notifications.error(format!("Could not reload the layout from {LAYOUT_PATH}")),
+    }
+}
@@ -0,0 +1,4403 @@
+use std::{borrow::Cow, hash::Hash, path::PathBuf};
+
+use bevy::{
+    app::AppExit,
+    core_pipeline::{
+        bloom::BloomSettings, clear_color::ClearColorConfig, tonemapping::Tonemapping,
+    },
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    math::vec2,
+    pbr::{
+        wireframe::{Wireframe, WireframePlugin},
+        FogFalloff, FogSettings,
+    },
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        primitives::Aabb,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureFormatFeatureFlags, TextureUsages,
+        },
+        renderer::RenderAdapter,
+    },
+    scene::SceneInstance,
+    utils::{HashMap, HashSet},
+    window::PrimaryWindow,
+    winit::{UpdateMode, WinitSettings},
+};
+use bevy_egui::{egui, EguiContext, EguiPlugin, EguiUserTextures};
+use egui_dock::{DockArea, NodeIndex, Style, TabIndex, Tree};
+use gizmo::Gizmo;
+use grid::GridMarker;
+use outline::{HoverOutline, SelectionOutline};
+use screenshot::{ScreenshotPlugin, ScreenshotRequests};
+use serde::{
+    de::{EnumAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+pub use asset_browser::AssetBrowserSettings;
+pub use console::{
+    ConsoleCommand, ConsoleCommands, ConsoleInput, ConsoleOutput, RegisterConsoleCommand,
+};
+pub use environment::EnvironmentSettings;
+pub use gizmo::{GizmoMode, GizmoSpace};
+pub use grid::{GridSettings, SnapSettings};
+pub use headless::render_frames;
+pub use history::{EditAction, EditHistory};
+pub use log_panel::{LogBuffer, LogLine, LogPanelPlugin};
+pub use minimap::{MinimapCorner, MinimapSettings};
+pub use notifications::{NotificationLevel, Notifications};
+pub use outline::OutlineSettings;
+pub use scene::{KeyLight, Primitive, SceneEditSettings, SceneFileSettings, SceneObject};
+pub use screenshot::{LastScreenshot, ScreenshotSettings};
+pub use tab_registry::{RegisterViewportTab, TabRegistry};
+pub use timeline::{Timeline, Track, TransformClip};
+
+mod asset_browser;
+mod console;
+mod environment;
+mod gizmo;
+mod grid;
+mod headless;
+mod history;
+mod inspector;
+#[cfg(feature = "layout-hot-reload")]
+mod layout_watcher;
+mod log_panel;
+mod minimap;
+mod notifications;
+mod outline;
+mod scene;
+mod screenshot;
+mod tab_registry;
+mod timeline;
+
+// adds a dockable editor ui with a 3d viewport to the app. the layout it starts with, the
+// viewport images and the ui's fonts can be changed with the builder methods
+pub struct DockingViewportPlugin {
+    layout: Tree<Tab>,
+    viewport_config: ViewportConfig,
+    fonts: Vec<(String, FontSource)>,
+    persistent: bool,
+}
+
+impl Default for DockingViewportPlugin {
+    fn default() -> Self {
+        Self {
+            layout: default_layout(),
+            viewport_config: default(),
+            fonts: Vec::new(),
+            persistent: true,
+        }
+    }
+}
+
+// a ttf or otf font for the ui, either read from a file when the app starts or built into the app
+#[derive(Clone)]
+pub enum FontSource {
+    Path(PathBuf),
+    Bytes(&'static [u8]),
+}
+
+impl DockingViewportPlugin {
+    // the layout used when there isn't a saved one, and which Reset Layout goes back to
+    pub fn with_layout(mut self, layout: Tree<Tab>) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    // the size of each viewport image until it's been resized to fit its tab
+    pub fn with_render_target_size(mut self, size: UVec2) -> Self {
+        self.viewport_config.default_size = size;
+        self
+    }
+
+    // how the viewport images are made, for when more than their size needs changing
+    pub fn with_viewport_config(mut self, config: ViewportConfig) -> Self {
+        self.viewport_config = config;
+        self
+    }
+
+    // use a font for the ui's text before egui's own ones. egui's are still there to fall back on
+    // for any characters it doesn't have, and adding more than one font makes each later one a
+    // fallback for the ones before it
+    pub fn with_font(mut self, name: impl Into<String>, source: FontSource) -> Self {
+        self.fonts.push((name.into(), source));
+        self
+    }
+
+    // don't load or save the layout, layout presets, preferences or camera bookmarks, so that the
+    // app starts the same way every time whatever's in the working directory
+    pub fn without_persistence(mut self) -> Self {
+        self.persistent = false;
+        self
+    }
+}
+
+impl Plugin for DockingViewportPlugin {
+    fn build(&self, app: &mut App) {
+        // the app might well be using egui for other things already
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        // used for the frame stats shown over the viewports
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        // drawing wireframes needs the POLYGON_MODE_LINE wgpu feature, so the app has to turn that
+        // on in the RenderPlugin's settings for the wireframe toggle to work
+        if !app.is_plugin_added::<WireframePlugin>() {
+            app.add_plugins(WireframePlugin);
+        }
+        app.add_plugins(ScreenshotPlugin)
+            .insert_resource(DockingViewportSettings {
+                layout: self.layout.clone(),
+                persistent: self.persistent,
+            })
+            .insert_resource(self.viewport_config.clone())
+            .insert_resource(UiFonts(self.fonts.clone()))
+            .add_systems(Startup, setup_docktree)
+            .add_systems(Startup, load_fonts)
+            .add_systems(Startup, check_viewport_format)
+            .add_systems(Startup, grid::setup_grid)
+            .add_systems(Startup, outline::setup_outline)
+            .add_systems(Startup, minimap::setup_minimap.after(check_viewport_format))
+            .init_resource::<ViewportInput>()
+            .init_resource::<ViewportRect>()
+            .init_resource::<ViewportHovered>()
+            .init_resource::<ViewportCameraSettings>()
+            .init_resource::<Viewports>()
+            .init_resource::<PendingViewLocks>()
+            .init_resource::<Selected>()
+            .init_resource::<Hovered>()
+            .init_resource::<PointerWorldPosition>()
+            .init_resource::<TabRegistry>()
+            .init_resource::<DockSettings>()
+            .init_resource::<PowerMode>()
+            .init_resource::<UiTheme>()
+            .init_resource::<CameraBookmarks>()
+            .init_resource::<LayoutPresets>()
+            .init_resource::<MaximizedTab>()
+            .insert_resource(FrameScene(true))
+            .init_resource::<SceneEditSettings>()
+            .init_resource::<SceneFileSettings>()
+            .init_resource::<scene::PendingSceneLoads>()
+            .init_resource::<scene::PendingTextureLoads>()
+            .init_resource::<scene::TextureDropTarget>()
+            .init_resource::<GizmoMode>()
+            .init_resource::<GizmoSpace>()
+            .init_resource::<SimulationState>()
+            .init_resource::<Notifications>()
+            .init_resource::<EditHistory>()
+            .init_resource::<RenderSettings>()
+            .init_resource::<WireframeState>()
+            .init_resource::<ShowWorldAxes>()
+            .init_resource::<ShowMenuBar>()
+            .init_resource::<EnvironmentSettings>()
+            .init_resource::<GridSettings>()
+            .init_resource::<SnapSettings>()
+            .init_resource::<OutlineSettings>()
+            .init_resource::<MinimapSettings>()
+            .init_resource::<ConsoleOutput>()
+            .init_resource::<ConsoleCommands>()
+            .init_resource::<AssetBrowserSettings>()
+            .init_resource::<Timeline>()
+            .add_event::<TabOpened>()
+            .add_event::<TabClosed>()
+            .add_event::<ViewportResized>()
+            .add_event::<SelectionChanged>()
+            .add_event::<ConsoleInput>()
+            .add_systems(Update, sync_viewports.before(update_ui))
+            .add_systems(Update, apply_theme.before(update_ui))
+            .add_systems(Update, update_ui)
+            .add_systems(Update, send_tab_events.after(update_ui))
+            .add_systems(Update, orbit_camera.after(update_ui))
+            .add_systems(Update, zoom_camera.after(update_ui))
+            .add_systems(Update, pan_camera.after(update_ui))
+            .add_systems(Update, pick_entity.after(update_ui))
+            .add_systems(Update, send_selection_events.after(pick_entity))
+            .add_systems(Update, focus_camera.after(update_ui))
+            .add_systems(Update, apply_view_preset.after(update_ui))
+            .add_systems(Update, apply_view_locks.after(update_ui))
+            .add_systems(Update, recall_bookmark.after(update_ui))
+            .add_systems(Update, frame_scene.after(update_ui))
+            .add_systems(Update, tween_camera.after(update_ui))
+            .add_systems(Update, apply_render_settings.after(update_ui))
+            .add_systems(Update, environment::check_environment_load.after(update_ui))
+            .add_systems(Update, environment::apply_environment.after(update_ui))
+            .add_systems(Update, apply_wireframe.after(update_ui))
+            .add_systems(Update, draw_selection_bounds.after(update_ui))
+            .add_systems(Update, draw_world_axes.after(update_ui))
+            .add_systems(Update, draw_point_lights.after(update_ui))
+            .add_systems(Update, draw_spot_lights.after(update_ui))
+            .add_systems(Update, apply_power_mode.after(update_ui))
+            .add_systems(Update, grid::update_grid.after(update_ui))
+            .add_systems(Update, minimap::update_minimap.after(update_ui))
+            .add_systems(Update, console::run_console_commands.after(update_ui))
+            .add_systems(Update, timeline::record_keyframes.after(update_ui))
+            .add_systems(
+                Update,
+                timeline::play_timeline.after(timeline::record_keyframes),
+            )
+            .add_systems(
+                Update,
+                timeline::apply_timeline.after(timeline::play_timeline),
+            )
+            .add_systems(Update, hover_entity.after(update_ui))
+            .add_systems(Update, history::record_edits.after(update_ui))
+            .add_systems(Update, outline::update_outline_materials.after(update_ui))
+            .add_systems(Update, outline::update_outline.after(send_selection_events))
+            .add_systems(Update, outline::update_hover_outline.after(hover_entity))
+            .add_systems(Update, scene::load_dropped_files.after(update_ui))
+            .add_systems(Update, scene::check_scene_loads.after(update_ui))
+            .add_systems(Update, scene::load_dropped_textures.after(update_ui))
+            .add_systems(Update, scene::check_texture_loads.after(update_ui));
+        if !self.persistent {
+            return;
+        }
+        app.add_systems(Startup, load_bookmarks)
+            .add_systems(Startup, load_layout_presets)
+            .add_systems(Startup, load_preferences)
+            // this runs at the very end of the frame so that it sees the app exiting
+            .add_systems(Last, save_layout)
+            .add_systems(Last, save_bookmarks)
+            .add_systems(Last, save_layout_presets)
+            .add_systems(Last, save_preferences);
+        // reloading the layout is for working on it, so it needs turning on with its feature
+        #[cfg(feature = "layout-hot-reload")]
+        app.add_systems(Startup, layout_watcher::watch_layout)
+            .add_systems(Update, layout_watcher::reload_layout.before(sync_viewports));
+    }
+}
+
+// the fonts the plugin was given, in the order they were added
+#[derive(Resource)]
+struct UiFonts(Vec<(String, FontSource)>);
+
+// what the plugin was configured with
+#[derive(Resource)]
+struct DockingViewportSettings {
+    layout: Tree<Tab>,
+    persistent: bool,
+}
+
+// stores the docktree containing all the tabs
+#[derive(Deref, DerefMut, Resource)]
+pub struct DockTree(pub Tree<Tab>);
+
+// every kind of tab that can be shown in the docktree
+#[derive(Clone, PartialEq, Serialize)]
+pub enum Tab {
+    Viewport(ViewportId),
+    SceneControl,
+    Hierarchy,
+    Inspector,
+    Transform,
+    Render,
+    Lighting,
+    Camera,
+    Stats,
+    Log,
+    Console,
+    Assets,
+    Timeline,
+    // any other tab, distinguished by its name
+    Custom(String),
+    // a registered tab from a saved layout, which the app doesn't register any more
+    Missing(String),
+}
+
+impl Tab {
+    // the name shown in the tab's title and in the window menu
+    pub fn name(&self) -> Cow<'_, str> {
+        match self {
+            // the first viewport is left unnumbered, since usually it's the only one
+            Tab::Viewport(ViewportId(0)) => "Viewport".into(),
+            Tab::Viewport(ViewportId(id)) => format!("Viewport {}", id + 1).into(),
+            Tab::SceneControl => "Scene Control".into(),
+            Tab::Hierarchy => "Hierarchy".into(),
+            Tab::Inspector => "Inspector".into(),
+            Tab::Transform => "Transform".into(),
+            Tab::Render => "Render".into(),
+            Tab::Lighting => "Lighting".into(),
+            Tab::Camera => "Camera".into(),
+            Tab::Stats => "Stats".into(),
+            Tab::Log => "Log".into(),
+            Tab::Console => "Console".into(),
+            Tab::Assets => "Assets".into(),
+            Tab::Timeline => "Timeline".into(),
+            Tab::Custom(name) => name.as_str().into(),
+            Tab::Missing(name) => format!("Missing: {name}").into(),
+        }
+    }
+}
+
+// a saved layout might have a built in tab in it which has since been taken out, so rather than
+// the whole layout failing to load, any variant that isn't known any more comes back as a
+// placeholder for it
+impl<'de> Deserialize<'de> for Tab {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Tab", TAB_VARIANTS, TabVisitor { legacy: false })
+    }
+}
+
+// a tab from one of the oldest layouts, from before there could be more than one viewport, when
+// the viewport tab didn't have an id. it's written back out as a regular tab
+#[derive(Serialize)]
+#[serde(transparent)]
+struct LegacyTab(Tab);
+
+impl<'de> Deserialize<'de> for LegacyTab {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_enum("Tab", TAB_VARIANTS, TabVisitor { legacy: true })
+            .map(LegacyTab)
+    }
+}
+
+const TAB_VARIANTS: &[&str] = &[
+    "Viewport",
+    "SceneControl",
+    "Hierarchy",
+    "Inspector",
+    "Transform",
+    "Render",
+    "Lighting",
+    "Camera",
+    "Stats",
+    "Log",
+    "Console",
+    "Assets",
+    "Timeline",
+    "Custom",
+    "Missing",
+];
+
+// a variant's name, read as an identifier like the derived impls do (a String would expect quotes)
+struct TabVariant(String);
+
+impl<'de> Deserialize<'de> for TabVariant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(TabVariantVisitor)
+    }
+}
+
+struct TabVariantVisitor;
+
+impl Visitor<'_> for TabVariantVisitor {
+    type Value = TabVariant;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("the name of a tab")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Self::Value, E> {
+        Ok(TabVariant(name.to_owned()))
+    }
+}
+
+struct TabVisitor {
+    // whether the viewport tab is the old unit variant, which is always the first viewport
+    legacy: bool,
+}
+
+impl<'de> Visitor<'de> for TabVisitor {
+    type Value = Tab;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a tab")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (TabVariant(name), variant) = data.variant()?;
+        let tab = match name.as_str() {
+            "Viewport" if self.legacy => Tab::Viewport(ViewportId(0)),
+            "Viewport" => return variant.newtype_variant().map(Tab::Viewport),
+            "Custom" => return variant.newtype_variant().map(Tab::Custom),
+            "Missing" => return variant.newtype_variant().map(Tab::Missing),
+            "SceneControl" => Tab::SceneControl,
+            "Hierarchy" => Tab::Hierarchy,
+            "Inspector" => Tab::Inspector,
+            "Transform" => Tab::Transform,
+            "Render" => Tab::Render,
+            "Lighting" => Tab::Lighting,
+            "Camera" => Tab::Camera,
+            "Stats" => Tab::Stats,
+            "Log" => Tab::Log,
+            "Console" => Tab::Console,
+            "Assets" => Tab::Assets,
+            "Timeline" => Tab::Timeline,
+            // the built in tabs have all been unit variants, so there's nothing more to read for
+            // one that's been removed
+            _ => {
+                info!("The {name} tab in the saved layout doesn't exist any more");
+                Tab::Missing(name)
+            }
+        };
+        variant.unit_variant()?;
+        Ok(tab)
+    }
+}
+
+// the tabs that can be toggled from the window menu, apart from the ones registered by the app
+const BUILT_IN_TABS: [Tab; 12] = [
+    Tab::SceneControl,
+    Tab::Hierarchy,
+    Tab::Inspector,
+    Tab::Transform,
+    Tab::Render,
+    Tab::Lighting,
+    Tab::Camera,
+    Tab::Stats,
+    Tab::Log,
+    Tab::Console,
+    Tab::Assets,
+    Tab::Timeline,
+];
+
+// identifies each viewport tab, so that it can be matched up with its own camera and image
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ViewportId(pub u32);
+
+// the camera and image behind every viewport tab that is currently open
+#[derive(Resource, Deref, DerefMut, Default)]
+struct Viewports(HashMap<ViewportId, ViewportTarget>);
+
+struct ViewportTarget {
+    // the image which the camera renders to, so that we can display it inside the tab
+    image: Handle<Image>,
+    // the id egui knows the image by, which is fixed for as long as the image is registered
+    texture_id: egui::TextureId,
+    camera: Entity,
+    // the size the image was last asked to be, so that resizing can wait until it settles
+    requested_size: Option<UVec2>,
+    // the time (in seconds since startup) when the requested size last changed
+    requested_at: f64,
+}
+
+// marker struct for the cameras which render to the viewports
+#[derive(Component)]
+struct ViewportCamera;
+
+// the point a viewport camera orbits around and zooms towards
+#[derive(Component, Deref, DerefMut, Default)]
+struct CameraPivot(Vec3);
+
+// keeps a viewport camera looking along one of the view presets, so dragging doesn't orbit it
+// (though it can still be panned and zoomed). it can be unlocked from the viewport's menu
+#[derive(Component)]
+struct LockedView(ViewPreset);
+
+// views to lock viewports to once their cameras have been spawned
+#[derive(Resource, Deref, DerefMut, Default)]
+struct PendingViewLocks(HashMap<ViewportId, ViewPreset>);
+
+// whether the scene is animating, and how fast. it's up to the app's own systems to respect this
+#[derive(Resource)]
+pub struct SimulationState {
+    pub playing: bool,
+    // multiplies how fast everything moves, so 1 is normal speed
+    pub speed: f32,
+}
+
+impl Default for SimulationState {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            speed: 1.0,
+        }
+    }
+}
+
+// settings for how the tabs in the dock behave
+#[derive(Resource, Default)]
+pub struct DockSettings {
+    // if this is false, the last viewport can't be closed, so there's always a 3d view of the scene
+    pub allow_closing_last_viewport: bool,
+}
+
+// how often the app redraws. the plugin owns the WinitSettings, replacing them whenever this changes,
+// so set this rather than inserting WinitSettings directly. they're re-inserted rather than edited,
+// and winit only picks up the new ones at its next update, which in the low power modes may not be
+// until the next input event
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PowerMode {
+    // redraw every frame no matter what, for animations or capturing video
+    Continuous,
+    // redraw every frame while the window is focused, but only on input while it isn't
+    #[default]
+    Reactive,
+    // only redraw on input (or when the ui asks for it), to save as much battery as possible
+    LowPower,
+}
+
+impl PowerMode {
+    const ALL: [PowerMode; 3] = [
+        PowerMode::Continuous,
+        PowerMode::Reactive,
+        PowerMode::LowPower,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            PowerMode::Continuous => "Continuous",
+            PowerMode::Reactive => "Reactive",
+            PowerMode::LowPower => "Low Power",
+        }
+    }
+
+    fn winit_settings(self) -> WinitSettings {
+        let low_power = UpdateMode::ReactiveLowPower {
+            max_wait: std::time::Duration::MAX,
+        };
+        match self {
+            PowerMode::Continuous => WinitSettings {
+                focused_mode: UpdateMode::Continuous,
+                unfocused_mode: UpdateMode::Continuous,
+                ..default()
+            },
+            PowerMode::Reactive => WinitSettings {
+                focused_mode: UpdateMode::Continuous,
+                unfocused_mode: low_power,
+                ..default()
+            },
+            PowerMode::LowPower => WinitSettings {
+                focused_mode: low_power,
+                unfocused_mode: low_power,
+                ..default()
+            },
+        }
+    }
+}
+
+// whether the ui is light or dark. the dock area's style is made from egui's, so it follows along.
+// it's kept between runs in the preferences file
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl UiTheme {
+    const ALL: [UiTheme; 2] = [UiTheme::Dark, UiTheme::Light];
+
+    fn name(self) -> &'static str {
+        match self {
+            UiTheme::Dark => "Dark",
+            UiTheme::Light => "Light",
+        }
+    }
+
+    fn visuals(self) -> egui::Visuals {
+        match self {
+            UiTheme::Dark => egui::Visuals::dark(),
+            UiTheme::Light => egui::Visuals::light(),
+        }
+    }
+}
+
+// settings for how the viewports are drawn, shared between all of them
+#[derive(Resource)]
+struct RenderSettings {
+    // what the viewport images get cleared to before the scene is drawn
+    clear_color: Color,
+    // whether the fps and frame time are shown in the corner of each viewport
+    show_frame_stats: bool,
+    // whether an arrow showing which way the key light shines is drawn in the corner
+    show_light_direction: bool,
+    // render in high dynamic range, which gets tonemapped down into the 8 bit viewport image
+    hdr: bool,
+    tonemapping: Tonemapping,
+    // bloom only works in hdr, so it's left off whenever hdr is
+    bloom: bool,
+    bloom_intensity: f32,
+    // whether the selected entity's bounding box is drawn, and in what color
+    show_bounds: bool,
+    bounds_color: Color,
+    fog: Fog,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            // the same grey as bevy's default clear color
+            clear_color: Color::rgb(0.4, 0.4, 0.4),
+            show_frame_stats: true,
+            show_light_direction: true,
+            hdr: false,
+            tonemapping: Tonemapping::default(),
+            bloom: false,
+            bloom_intensity: BloomSettings::default().intensity,
+            show_bounds: false,
+            bounds_color: Color::rgb(0.2, 0.9, 0.9),
+            fog: Fog::default(),
+        }
+    }
+}
+
+// the fog the viewports are drawn with, if it's turned on. both falloffs' settings are kept, so
+// switching between them doesn't lose anything
+#[derive(Clone, Copy, PartialEq)]
+struct Fog {
+    enabled: bool,
+    color: Color,
+    falloff: FogMode,
+    // where linear fog starts and where it becomes completely opaque, in world units
+    start: f32,
+    end: f32,
+    // how quickly exponential fog thickens with distance
+    density: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: Color::rgb(0.5, 0.5, 0.6),
+            falloff: FogMode::Linear,
+            start: 10.,
+            end: 100.,
+            density: 0.05,
+        }
+    }
+}
+
+impl Fog {
+    fn settings(&self) -> FogSettings {
+        FogSettings {
+            color: self.color,
+            falloff: match self.falloff {
+                FogMode::Linear => FogFalloff::Linear {
+                    start: self.start,
+                    end: self.end,
+                },
+                FogMode::Exponential => FogFalloff::Exponential {
+                    density: self.density,
+                },
+            },
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FogMode {
+    Linear,
+    Exponential,
+}
+
+// whether the scene is drawn as a wireframe. only the scene objects get the wireframe, so the grid
+// and egui are drawn as normal
+#[derive(Resource, Deref, DerefMut, Default)]
+struct WireframeState(bool);
+
+// whether the menu bar is shown along the top of the window. hiding it (with F10, or from the
+// window menu) leaves more room for the tabs, and everything essential in it has a shortcut too
+#[derive(Resource, Deref, DerefMut)]
+pub struct ShowMenuBar(pub bool);
+
+impl Default for ShowMenuBar {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// whether lines along the world's axes are drawn out from the origin, in red, green and blue
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct ShowWorldAxes(pub bool);
+
+// views of the scene saved from the camera tab, which can be jumped back to from there or with alt
+// and a number key in a viewport. they're kept between runs, in their own file next to the layout
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct CameraBookmarks(pub Vec<Transform>);
+
+// sent with the name of a tab (as shown on it) whenever it's opened, however that happened, so
+// that the app can start doing whatever the tab needs. the tabs in the layout the app starts with
+// count as being opened too
+#[derive(Event)]
+pub struct TabOpened(pub String);
+
+// sent with the name of a tab whenever it's closed
+#[derive(Event)]
+pub struct TabClosed(pub String);
+
+// sent whenever a viewport's image is resized to fit its tab, with its new size in pixels
+#[derive(Event)]
+pub struct ViewportResized {
+    pub viewport: ViewportId,
+    pub width: u32,
+    pub height: u32,
+}
+
+// while a tab is maximized to fill the dock area, the layout from before it was, so that it can be
+// put back exactly as it was
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct MaximizedTab(pub Option<Tree<Tab>>);
+
+// layouts saved under a name from the window menu, which can be switched to from there. like the
+// bookmarks, they're kept between runs in their own file
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct LayoutPresets(pub HashMap<String, Tree<Tab>>);
+
+// set this to move every viewport's camera to fit the whole scene in view, which happens as soon as
+// everything in the scene has been spawned. it starts off set, and gets set again whenever a scene
+// is opened or a model is loaded
+#[derive(Resource, Deref, DerefMut)]
+pub struct FrameScene(pub bool);
+
+// the entity currently selected for editing, if any
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct Selected(pub Option<Entity>);
+
+// sent whenever a different entity (or nothing) gets selected, however that happened
+#[derive(Event)]
+pub struct SelectionChanged(pub Option<Entity>);
+
+// the entity under the pointer in the viewport, if any
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct Hovered(pub Option<Entity>);
+
+// the point in the world under the pointer in the viewport, if any. that's where it hits a mesh, or
+// the ground if it misses everything
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct PointerWorldPosition(pub Option<Vec3>);
+
+// mouse input captured by the viewport tab this frame, which the camera systems then consume
+#[derive(Resource, Default)]
+struct ViewportInput {
+    // the camera of the viewport the input came from
+    camera: Option<Entity>,
+    orbit: Vec2,
+    zoom: f32,
+    pan: Vec2,
+    // where the viewport was clicked this frame, in normalized device coordinates
+    click: Option<Vec2>,
+    // where the pointer is over the viewport this frame, if it is, in normalized device coordinates
+    hover: Option<Vec2>,
+    // whether the camera should reframe the selection
+    focus: bool,
+    view_preset: Option<ViewPreset>,
+    // which camera bookmark to jump to, if any
+    bookmark: Option<usize>,
+}
+
+// whether the pointer is over one of the viewports this frame (or dragging something that started
+// in one). egui doesn't count a widget as hovered while something else is on top of it (a window, a
+// popup, etc) or while another widget is being dragged, so this is false whenever the pointer is
+// meant for some other part of the ui. egui's wants_pointer_input can't be used for this, since the
+// viewport image is itself an egui widget and so it's always true over the viewport
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct ViewportHovered(pub bool);
+
+// where the image of the viewport in use sits in the window, in logical pixels (the same as egui's
+// points and bevy's cursor positions)
+#[derive(Resource, Default, Clone, Copy)]
+pub struct ViewportRect {
+    pub min: Vec2,
+    pub size: Vec2,
+    // how many physical pixels there are per logical pixel
+    pub scale_factor: f64,
+}
+
+impl ViewportRect {
+    pub fn contains(&self, window_pos: Vec2) -> bool {
+        let local = self.to_local(window_pos);
+        local.cmpge(Vec2::ZERO).all() && local.cmplt(self.size).all()
+    }
+
+    // relative to the top left corner of the viewport, still in logical pixels
+    pub fn to_local(&self, window_pos: Vec2) -> Vec2 {
+        window_pos - self.min
+    }
+
+    // relative to the top left corner of the viewport, in physical pixels of the render target
+    pub fn to_physical(&self, window_pos: Vec2) -> Vec2 {
+        self.to_local(window_pos) * self.scale_factor as f32
+    }
+
+    // in normalized device coordinates, which is what the camera needs to cast rays from
+    pub fn to_ndc(&self, window_pos: Vec2) -> Vec2 {
+        let fraction = self.to_local(window_pos) / self.size;
+        vec2(fraction.x * 2. - 1., 1. - fraction.y * 2.)
+    }
+}
+
+// the standard axis aligned views, each looking at the origin
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ViewPreset {
+    Top,
+    Bottom,
+    Front,
+    Back,
+    Right,
+    Left,
+}
+
+impl ViewPreset {
+    const ALL: [ViewPreset; 6] = [
+        ViewPreset::Top,
+        ViewPreset::Bottom,
+        ViewPreset::Front,
+        ViewPreset::Back,
+        ViewPreset::Right,
+        ViewPreset::Left,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ViewPreset::Top => "Top",
+            ViewPreset::Bottom => "Bottom",
+            ViewPreset::Front => "Front",
+            ViewPreset::Back => "Back",
+            ViewPreset::Right => "Right",
+            ViewPreset::Left => "Left",
+        }
+    }
+
+    // which side of the origin the camera sits on
+    fn direction(self) -> Vec3 {
+        match self {
+            ViewPreset::Top => Vec3::Y,
+            ViewPreset::Bottom => Vec3::NEG_Y,
+            ViewPreset::Front => Vec3::Z,
+            ViewPreset::Back => Vec3::NEG_Z,
+            ViewPreset::Right => Vec3::X,
+            ViewPreset::Left => Vec3::NEG_X,
+        }
+    }
+
+    // looking straight up or down, y can't be up on screen, so the front goes at the bottom instead
+    fn up(self) -> Vec3 {
+        match self {
+            ViewPreset::Top => Vec3::NEG_Z,
+            ViewPreset::Bottom => Vec3::Z,
+            _ => Vec3::Y,
+        }
+    }
+}
+
+// how long the requested viewport size has to stay the same before the image is actually resized,
+// so that dragging a dock splitter doesn't reallocate the texture every single frame
+const RESIZE_DEBOUNCE_SECS: f64 = 0.1;
+
+// tweakable settings for how the viewport camera responds to input. the speeds (and inverting the
+// scroll direction) can be changed from the camera tab, and are kept in the preferences file
+#[derive(Resource)]
+struct ViewportCameraSettings {
+    // how far the camera rotates per pixel of mouse drag (in radians)
+    orbit_speed: f32,
+    // how far the camera moves per pixel of mouse drag when panning, per unit of distance from the
+    // pivot (so panning feels the same no matter how far zoomed in or out the camera is)
+    pan_speed: f32,
+    // how far the camera moves per point of scrolling
+    zoom_speed: f32,
+    // scroll up to zoom out rather than in
+    invert_scroll: bool,
+    // the closest the camera is allowed to get to its pivot, so it can never zoom through it
+    min_distance: f32,
+    // whether jumps like focusing on the selection glide there rather than snapping
+    smooth_moves: bool,
+    // how long a smooth move takes, in seconds
+    move_duration: f32,
+}
+
+impl Default for ViewportCameraSettings {
+    fn default() -> Self {
+        Self {
+            orbit_speed: 0.01,
+            pan_speed: 0.0015,
+            zoom_speed: 0.05,
+            invert_scroll: false,
+            min_distance: 1.0,
+            smooth_moves: true,
+            move_duration: 0.3,
+        }
+    }
+}
+
+// how the viewport images themselves are made
+#[derive(Resource, Clone)]
+pub struct ViewportConfig {
+    // the size each image starts at, until it's been resized to fit its tab
+    pub default_size: UVec2,
+    // how many pixels the viewport image has per physical pixel on screen. below 1 renders faster
+    // at a lower resolution, and above 1 supersamples the scene for smoother edges
+    pub render_scale: f32,
+    // e.g. Rgba8UnormSrgb to save swizzling screenshots, or a float format for hdr. it has to be
+    // one that can be rendered to and drawn by egui, otherwise the default is used instead
+    pub format: TextureFormat,
+}
+
+impl Default for ViewportConfig {
+    fn default() -> Self {
+        Self {
+            default_size: UVec2::new(512, 512),
+            render_scale: 1.0,
+            format: TextureFormat::Bgra8UnormSrgb,
+        }
+    }
+}
+
+// a move the camera is part way through
+#[derive(Component)]
+struct CameraTween {
+    start: Transform,
+    target: Transform,
+    duration: f32,
+    elapsed: f32,
+    // what the tween last set the transform to, so that it can tell if something else has moved
+    // the camera since
+    last: Transform,
+}
+
+// stop just short of straight up/down so the camera never flips over the poles
+const MAX_PITCH: f32 = 1.5;
+// keep the key light from pointing straight up or down, where its yaw can't be worked out (degrees)
+const MAX_LIGHT_PITCH: f32 = 89.;
+// the radius of the light direction overlay in the corner of the viewport, in points
+const LIGHT_ARROW_SIZE: f32 = 24.;
+// the length of the axes drawn in the corner of the viewport, and how big their ends are, in points
+const AXES_SIZE: f32 = 30.;
+const AXES_HANDLE_RADIUS: f32 = 8.;
+// pressed with alt to jump to the first nine camera bookmarks
+const BOOKMARK_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+// the shadow map resolutions that can be picked from in the lighting tab
+const SHADOW_MAP_SIZES: [usize; 3] = [1024, 2048, 4096];
+// this tells egui how to render each tab
+struct TabViewer<'a> {
+    // the tabs fetch whatever data they need straight out of the world, so that any tab can get at
+    // anything it needs (e.g. the inspector, which can edit any component)
+    world: &'a mut World,
+    // add into here any other data that needs to be passed into any tabs
+    window_scale_factor: f64,
+    tab_registry: &'a TabRegistry,
+    // whether viewport tabs can be closed at the moment
+    viewports_closeable: bool,
+    // the smoothed fps and frame time (in milliseconds), if they've been measured yet
+    frame_stats: Option<(f64, f64)>,
+}
+
+impl egui_dock::TabViewer for TabViewer<'_> {
+    type Tab = Tab;
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        // we can do different things inside the tab depending on which tab it is
+        match tab {
+            Tab::Viewport(id) => self.viewport_ui(ui, *id),
+            Tab::SceneControl => self.scene_control_ui(ui),
+            Tab::Hierarchy => self.hierarchy_ui(ui),
+            Tab::Inspector => self.inspector_ui(ui),
+            Tab::Transform => self.transform_ui(ui),
+            Tab::Render => self.render_ui(ui),
+            Tab::Lighting => self.lighting_ui(ui),
+            Tab::Camera => {
+                self.camera_controls_ui(ui);
+                ui.separator();
+                self.clip_planes_ui(ui);
+                ui.separator();
+                self.bookmarks_ui(ui);
+            }
+            Tab::Stats => self.stats_ui(ui),
+            Tab::Log => log_panel::log_ui(ui, self.world),
+            Tab::Console => console::console_ui(ui, self.world),
+            Tab::Assets => asset_browser::asset_browser_ui(ui, self.world),
+            Tab::Timeline => timeline::timeline_ui(ui, self.world),
+            Tab::Custom(name) => match self.tab_registry.get(name) {
+                Some(tab_ui) => tab_ui(ui, self.world),
+                // any other tab will just show this basic default UI
+                None => {
+                    ui.label(format!("Content of {name}"));
+                }
+            },
+            Tab::Missing(name) => {
+                ui.label(format!(
+                    "The {name} tab from the saved layout doesn't exist any more, so this can be \
+                     closed"
+                ));
+            }
+        };
+    }
+    // show the title of the tab
+    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        tab.name().into_owned().into()
+    }
+    fn closeable(&mut self, tab: &mut Self::Tab) -> bool {
+        match tab {
+            Tab::Viewport(_) => self.viewports_closeable,
+            _ => true,
+        }
+    }
+    fn on_close(&mut self, tab: &mut Self::Tab) -> bool {
+        // stop the camera rendering straight away, rather than waiting for sync_viewports to get rid
+        // of it next frame
+        if let Tab::Viewport(id) = tab {
+            let camera = self
+                .world
+                .resource::<Viewports>()
+                .get(id)
+                .map(|target| target.camera);
+            if let Some(mut camera) = camera.and_then(|camera| self.world.get_mut::<Camera>(camera))
+            {
+                camera.is_active = false;
+            }
+        }
+        true
+    }
+}
+
+impl TabViewer<'_> {
+    fn viewport_ui(&mut self, ui: &mut egui::Ui, id: ViewportId) {
+        // the camera and image get created by sync_viewports, so won't exist yet on the very first
+        // frame the tab is open
+        let Some(target) = self.world.resource::<Viewports>().get(&id) else {
+            return;
+        };
+        let (image, texture_id, camera) = (target.image.clone(), target.texture_id, target.camera);
+
+        let viewport_size = vec2(ui.available_width(), ui.available_height());
+        // the image is still drawn at the tab's size, however many pixels it's rendered with
+        let render_scale = self.world.resource::<ViewportConfig>().render_scale;
+        let scale_factor = self.window_scale_factor * render_scale as f64;
+        // resize the viewport if needed (leaving it alone while the tab is tiny)
+        if let Some(target_size) = viewport_target_size(viewport_size, scale_factor) {
+            let time = self.world.resource::<Time>().elapsed_seconds_f64();
+            let mut viewports = self.world.resource_mut::<Viewports>();
+            let Some(target) = viewports.get_mut(&id) else {
+                return;
+            };
+            if target.requested_size != Some(target_size) {
+                target.requested_size = Some(target_size);
+                target.requested_at = time;
+            }
+            // wait for the size to settle, unless the user has already let go of whatever they were
+            // dragging
+            let settled = time - target.requested_at >= RESIZE_DEBOUNCE_SECS;
+            let dragging = ui.input(|input| input.pointer.any_down());
+
+            let mut images = self.world.resource_mut::<Assets<Image>>();
+            let Some(viewport_image) = images.get_mut(&image) else {
+                self.world.resource_mut::<Notifications>().error(format!(
+                    "The image for the {} tab is missing",
+                    Tab::Viewport(id).name()
+                ));
+                return;
+            };
+            if viewport_image.size().as_uvec2() != target_size && (settled || !dragging) {
+                let size = Extent3d {
+                    width: target_size.x,
+                    height: target_size.y,
+                    ..default()
+                };
+                viewport_image.resize(size);
+                trace!("resized viewport image to {}x{}", size.width, size.height);
+                self.world
+                    .resource_mut::<Events<ViewportResized>>()
+                    .send(ViewportResized {
+                        viewport: id,
+                        width: size.width,
+                        height: size.height,
+                    });
+            }
+        }
+        // show the viewport image, and make it respond to mouse drags
+        let response = ui
+            .image(texture_id, viewport_size.to_array())
+            .interact(egui::Sense::click_and_drag());
+        // right clicking the viewport brings up a menu of things to do with it
+        response.context_menu(|ui| {
+            if ui.button("Screenshot").clicked() {
+                self.world
+                    .resource_mut::<ScreenshotRequests>()
+                    .push(image.clone());
+                ui.close_menu();
+            }
+            ui.menu_button("Gizmo Space", |ui| {
+                let mut gizmo_space = self.world.resource_mut::<GizmoSpace>();
+                for space in [GizmoSpace::World, GizmoSpace::Local] {
+                    if ui
+                        .selectable_value(&mut *gizmo_space, space, space.name())
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.menu_button("View", |ui| {
+                for preset in ViewPreset::ALL {
+                    if ui.button(preset.name()).clicked() {
+                        let mut viewport_input = self.world.resource_mut::<ViewportInput>();
+                        viewport_input.camera = Some(camera);
+                        viewport_input.view_preset = Some(preset);
+                        ui.close_menu();
+                    }
+                }
+            });
+            if let Some(locked) = self.world.get::<LockedView>(camera) {
+                if ui
+                    .button(format!("Unlock {} View", locked.0.name()))
+                    .clicked()
+                {
+                    self.world.entity_mut(camera).remove::<LockedView>();
+                    ui.close_menu();
+                }
+            }
+        });
+
+        if let Some((fps, frame_time)) = self.frame_stats {
+            if self.world.resource::<RenderSettings>().show_frame_stats {
+                ui.painter_at(response.rect).text(
+                    response.rect.left_top() + egui::vec2(6., 6.),
+                    egui::Align2::LEFT_TOP,
+                    format!("{fps:.0} fps\n{frame_time:.2} ms"),
+                    egui::FontId::monospace(12.),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+        if self.world.resource::<RenderSettings>().show_light_direction {
+            self.light_direction_ui(ui, response.rect, camera);
+        }
+        minimap::minimap_ui(ui, self.world, response.rect);
+        let axes_preset = self.axes_ui(ui, &response, camera);
+        if response.hovered() {
+            // this is from where the pointer was last frame, but that's close enough for reading
+            if let Some(position) = **self.world.resource::<PointerWorldPosition>() {
+                ui.painter_at(response.rect).text(
+                    // the light direction arrow can be in the bottom right corner
+                    response.rect.center_bottom() + egui::vec2(0., -6.),
+                    egui::Align2::CENTER_BOTTOM,
+                    format!("{:.2}, {:.2}, {:.2}", position.x, position.y, position.z),
+                    egui::FontId::monospace(12.),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+        // the gizmo mode isn't always obvious from the gizmo alone (or at all, with no gizmo)
+        ui.painter_at(response.rect).text(
+            response.rect.left_bottom() + egui::vec2(6., -6.),
+            egui::Align2::LEFT_BOTTOM,
+            format!(
+                "{} ({})",
+                self.world.resource::<GizmoMode>().name(),
+                self.world.resource::<GizmoSpace>().name()
+            ),
+            egui::FontId::monospace(12.),
+            egui::Color32::WHITE,
+        );
+
+        // switch gizmo mode (or space, with X) with the keyboard while hovering the viewport, with Q
+        // hiding it
+        if response.hovered() {
+            let mut gizmo_mode = self.world.resource_mut::<GizmoMode>();
+            ui.input(|input| {
+                if input.key_pressed(egui::Key::Q) {
+                    *gizmo_mode = GizmoMode::None;
+                } else if input.key_pressed(egui::Key::W) {
+                    *gizmo_mode = GizmoMode::Translate;
+                } else if input.key_pressed(egui::Key::E) {
+                    *gizmo_mode = GizmoMode::Rotate;
+                } else if input.key_pressed(egui::Key::R) {
+                    *gizmo_mode = GizmoMode::Scale;
+                }
+            });
+            if ui.input(|input| input.key_pressed(egui::Key::X)) {
+                let mut gizmo_space = self.world.resource_mut::<GizmoSpace>();
+                *gizmo_space = match *gizmo_space {
+                    GizmoSpace::World => GizmoSpace::Local,
+                    GizmoSpace::Local => GizmoSpace::World,
+                };
+            }
+        }
+        if response.hovered() && ui.input(|input| input.key_pressed(egui::Key::Delete)) {
+            self.delete_selected();
+        }
+        if response.hovered() && ui.input(duplicate_shortcut) {
+            self.duplicate_selected();
+        }
+        // draw the gizmo over the selected entity - if a drag grabs one of its handles then the drag
+        // is used for that rather than for orbiting the camera
+        let gizmo_in_use = self.gizmo_ui(ui, &response, camera);
+
+        let mut viewport_input = self.world.resource_mut::<ViewportInput>();
+        let viewport_rect = ViewportRect {
+            min: vec2(response.rect.min.x, response.rect.min.y),
+            size: vec2(response.rect.width(), response.rect.height()),
+            scale_factor: self.window_scale_factor,
+        };
+        // whichever viewport is being used gets its own camera moved
+        let in_use = response.hovered() || response.dragged();
+        if in_use {
+            viewport_input.camera = Some(camera);
+        }
+        if response.hovered() && ui.input(|input| input.key_pressed(egui::Key::F)) {
+            viewport_input.focus = true;
+        }
+        // the same keys as blender uses for the view presets, holding ctrl for the opposite side
+        if response.hovered() {
+            let preset = ui.input(|input| {
+                let opposite = input.modifiers.ctrl;
+                // alt and a number is for the camera bookmarks instead
+                if input.modifiers.alt {
+                    None
+                } else if input.key_pressed(egui::Key::Num1) {
+                    Some(if opposite {
+                        ViewPreset::Back
+                    } else {
+                        ViewPreset::Front
+                    })
+                } else if input.key_pressed(egui::Key::Num3) {
+                    Some(if opposite {
+                        ViewPreset::Left
+                    } else {
+                        ViewPreset::Right
+                    })
+                } else if input.key_pressed(egui::Key::Num7) {
+                    Some(if opposite {
+                        ViewPreset::Bottom
+                    } else {
+                        ViewPreset::Top
+                    })
+                } else {
+                    None
+                }
+            });
+            if preset.is_some() {
+                viewport_input.view_preset = preset;
+            }
+            let bookmark = ui.input(|input| {
+                input
+                    .modifiers
+                    .alt
+                    .then(|| BOOKMARK_KEYS.iter().position(|&key| input.key_pressed(key)))
+                    .flatten()
+            });
+            if bookmark.is_some() {
+                viewport_input.bookmark = bookmark;
+            }
+        }
+        // only orbit while the pointer is actually inside the viewport, since egui keeps reporting a
+        // drag even once the pointer has left the widget
+        if response.dragged_by(egui::PointerButton::Primary)
+            && !gizmo_in_use
+            && ui.rect_contains_pointer(response.rect)
+        {
+            let delta = response.drag_delta();
+            viewport_input.orbit += vec2(delta.x, delta.y);
+        }
+        if response.dragged_by(egui::PointerButton::Middle)
+            && ui.rect_contains_pointer(response.rect)
+        {
+            let delta = response.drag_delta();
+            viewport_input.pan += vec2(delta.x, delta.y);
+        }
+        // clicking selects whatever is under the pointer. the position is passed on in normalized
+        // device coordinates, which means it doesn't matter what scale factor the image is being
+        // rendered at
+        if axes_preset.is_some() {
+            viewport_input.view_preset = axes_preset;
+        } else if response.clicked_by(egui::PointerButton::Primary) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                viewport_input.click = Some(viewport_rect.to_ndc(vec2(pos.x, pos.y)));
+            }
+        }
+        if response.hovered() {
+            viewport_input.hover = response
+                .hover_pos()
+                .map(|pos| viewport_rect.to_ndc(vec2(pos.x, pos.y)));
+        }
+        // only take the scroll while hovering the viewport, so scrolling elsewhere still works as
+        // normal
+        if response.hovered() {
+            let scroll = ui.input_mut(|input| std::mem::take(&mut input.scroll_delta));
+            viewport_input.zoom += scroll.y;
+        }
+        // let systems working in window coordinates know where the viewport in use is
+        if in_use {
+            *self.world.resource_mut::<ViewportRect>() = viewport_rect;
+            **self.world.resource_mut::<ViewportHovered>() = true;
+        }
+    }
+
+    // returns whether the gizmo is currently being dragged
+    fn gizmo_ui(&mut self, ui: &egui::Ui, response: &egui::Response, camera: Entity) -> bool {
+        // the gizmo needs to know how the viewport camera sees the world to draw over the top of it
+        let (Some(camera), Some(camera_transform)) = (
+            self.world.get::<Camera>(camera),
+            self.world.get::<GlobalTransform>(camera),
+        ) else {
+            return false;
+        };
+        let gizmo = Gizmo {
+            id: response.id.with("gizmo"),
+            mode: *self.world.resource::<GizmoMode>(),
+            space: *self.world.resource::<GizmoSpace>(),
+            // holding ctrl snaps too, for when snapping is only wanted now and then
+            snap: {
+                let snap = *self.world.resource::<SnapSettings>();
+                (snap.enabled || ui.input(|input| input.modifiers.ctrl)).then_some(snap)
+            },
+            view_projection: camera.projection_matrix()
+                * camera_transform.compute_matrix().inverse(),
+            camera_transform: *camera_transform,
+            rect: response.rect,
+        };
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            return false;
+        };
+        let Some(&local) = self.world.get::<Transform>(selected) else {
+            return false;
+        };
+        // the gizmo works in world space, so something with a parent (like a mesh in a loaded
+        // gltf) is drawn and dragged where it actually is, then moved back into its parent's space
+        let parent = self
+            .world
+            .get::<Parent>(selected)
+            .and_then(|parent| self.world.get::<GlobalTransform>(parent.get()))
+            .copied();
+        let mut transform = match parent {
+            Some(parent) => parent.mul_transform(local).compute_transform(),
+            None => local,
+        };
+        let before = transform;
+        let active = gizmo.interact(ui, response, &mut transform);
+        // only write it back when it's moved, so the transform isn't flagged as changed every frame
+        if transform != before {
+            let transform = match parent {
+                Some(parent) => Transform::from_matrix(
+                    parent.compute_matrix().inverse() * transform.compute_matrix(),
+                ),
+                None => transform,
+            };
+            if let Some(mut local) = self.world.get_mut::<Transform>(selected) {
+                *local = transform;
+            }
+            self.world.resource_mut::<EditHistory>().mark_edited();
+        }
+        active
+    }
+
+    fn scene_control_ui(&mut self, ui: &mut egui::Ui) {
+        let mut simulation = self.world.resource_mut::<SimulationState>();
+        ui.horizontal(|ui| {
+            let label = if simulation.playing { "Pause" } else { "Play" };
+            if ui
+                .button(label)
+                .on_hover_text("Start or stop the app's animations")
+                .clicked()
+            {
+                simulation.playing = !simulation.playing;
+            }
+            ui.add(egui::Slider::new(&mut simulation.speed, 0.0..=5.0).text("Speed"))
+                .on_hover_text("How fast the animations play, 1 being normal speed");
+        });
+        ui.separator();
+        self.camera_ui(ui);
+        ui.separator();
+
+        // as an example we get the selected entity's material so it can be edited in the UI
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            ui.label("No selection");
+            return;
+        };
+        let Some(handle) = self
+            .world
+            .get::<Handle<StandardMaterial>>(selected)
+            .cloned()
+        else {
+            ui.label("The selected entity has no material");
+            return;
+        };
+        // edit copies, so the material only gets flagged as changed (which has bevy prepare it
+        // again) when it actually is
+        let Some(material) = self
+            .world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&handle)
+        else {
+            return;
+        };
+        let mut base_color = material.base_color;
+        let mut alpha_mode = material.alpha_mode;
+        let mut metallic = material.metallic;
+        let mut roughness = material.perceptual_roughness;
+        let mut reflectance = material.reflectance;
+        let old_emissive = material.emissive;
+        ui.horizontal(|ui| {
+            ui.label("Edit Color:")
+                .on_hover_text("Base color of the selected material");
+            base_color = Self::color_picker_ui(ui, handle.id(), base_color);
+        });
+        // the alpha is kept whatever the alpha mode, but it only does anything with one that blends
+        if base_color.a() < 1. && alpha_mode == AlphaMode::Opaque {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Opaque materials ignore the alpha").weak());
+                if ui
+                    .button("Blend")
+                    .on_hover_text("Blend the material with what's behind it using its alpha")
+                    .clicked()
+                {
+                    alpha_mode = AlphaMode::Blend;
+                }
+            });
+        }
+        ui.add(egui::Slider::new(&mut metallic, 0.0..=1.0).text("Metallic"))
+            .on_hover_text("How much the material behaves like a metal rather than a plastic");
+        ui.add(egui::Slider::new(&mut roughness, 0.0..=1.0).text("Roughness"))
+            .on_hover_text("How rough the surface is, from mirror-like at 0 to completely matte");
+        ui.add(egui::Slider::new(&mut reflectance, 0.0..=1.0).text("Reflectance"))
+            .on_hover_text("How much light non-metals reflect when looked at head on");
+
+        // emission is edited as a color and an intensity, so that it can go brighter than white.
+        // they're remembered between frames since the color can't be worked out from a black
+        // emissive, unless the material has been changed from somewhere else in the meantime
+        let state_id = ui.id().with(("emissive", handle.id()));
+        let (mut emissive_color, mut intensity) = ui
+            .data(|data| data.get_temp::<([f32; 3], f32)>(state_id))
+            .filter(|&(color, intensity)| emissive(color, intensity) == old_emissive)
+            .unwrap_or_else(|| split_emissive(old_emissive));
+        let old_emissive_state = (emissive_color, intensity);
+        ui.horizontal(|ui| {
+            ui.label("Emissive:");
+            ui.color_edit_button_rgb(&mut emissive_color)
+                .on_hover_text("The color of the light the material gives off by itself");
+        });
+        ui.add(egui::Slider::new(&mut intensity, 0.0..=10.0).text("Emissive Intensity"))
+            .on_hover_text(
+                "How brightly the material glows, with anything above 1 brighter than white",
+            );
+        ui.data_mut(|data| data.insert_temp(state_id, (emissive_color, intensity)));
+
+        let color_changed = base_color != material.base_color;
+        // the emissive is only written back when it's been edited, since going through the color
+        // and intensity can come back very slightly different
+        let emissive_changed = (emissive_color, intensity) != old_emissive_state;
+        if !color_changed
+            && !emissive_changed
+            && alpha_mode == material.alpha_mode
+            && metallic == material.metallic
+            && roughness == material.perceptual_roughness
+            && reflectance == material.reflectance
+        {
+            return;
+        }
+        let mut materials = self.world.resource_mut::<Assets<StandardMaterial>>();
+        let Some(material) = materials.get_mut(&handle) else {
+            return;
+        };
+        material.base_color = base_color;
+        material.alpha_mode = alpha_mode;
+        material.metallic = metallic;
+        material.perceptual_roughness = roughness;
+        material.reflectance = reflectance;
+        if emissive_changed {
+            material.emissive = emissive(emissive_color, intensity);
+        }
+        // only the color is undoable for now
+        if color_changed {
+            self.world.resource_mut::<EditHistory>().mark_edited();
+        }
+    }
+
+    // a color button with a hex field beside it for typing in an exact color. both edit the color
+    // without premultiplying the alpha, which is how bevy's colors (and so base_color) store it. a
+    // hex that isn't a valid color is ignored, and gets replaced by the color's own hex once the
+    // field loses focus
+    fn color_picker_ui(ui: &mut egui::Ui, id_source: impl Hash, color: Color) -> Color {
+        let mut edited = color;
+        let mut rgba = color.as_rgba_f32();
+        if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+            edited = rgba.into();
+        }
+        // whatever's typed is kept while it's being edited, even if it isn't a valid color yet
+        let hex_id = ui.id().with(("color_hex", id_source));
+        let mut hex = ui
+            .data(|data| data.get_temp::<String>(hex_id))
+            .unwrap_or_else(|| color_hex(edited));
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut hex)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(80.),
+        );
+        if response.changed() {
+            if let Ok(parsed) = Color::hex(hex.trim().trim_start_matches('#')) {
+                edited = parsed;
+            }
+        }
+        if response.has_focus() {
+            ui.data_mut(|data| data.insert_temp(hex_id, hex));
+        } else {
+            ui.data_mut(|data| data.remove::<String>(hex_id));
+        }
+        edited
+    }
+
+    fn hierarchy_ui(&mut self, ui: &mut egui::Ui) {
+        // list everything in the scene, falling back to the id for unnamed entities
+        let mut entities: Vec<_> = self
+            .world
+            .query_filtered::<(Entity, Option<&Name>), (
+                With<Transform>,
+                Without<SelectionOutline>,
+                Without<HoverOutline>,
+            )>()
+            .iter(self.world)
+            .map(|(entity, name)| {
+                let name =
+                    name.map_or_else(|| format!("Entity {entity:?}"), |name| name.to_string());
+                (entity, name)
+            })
+            .collect();
+        entities.sort_by_key(|(entity, _)| *entity);
+
+        let hovered = ui.ui_contains_pointer();
+        ui.horizontal(|ui| {
+            let delete_pressed = ui.input(|input| input.key_pressed(egui::Key::Delete));
+            if ui.button("Delete").clicked() || (hovered && delete_pressed) {
+                self.delete_selected();
+            }
+            if ui.button("Duplicate").clicked() || (hovered && ui.input(duplicate_shortcut)) {
+                self.duplicate_selected();
+            }
+        });
+        ui.separator();
+
+        let mut selected = self.world.resource_mut::<Selected>();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (entity, name) in entities {
+                if ui
+                    .selectable_label(**selected == Some(entity), name)
+                    .clicked()
+                {
+                    **selected = Some(entity);
+                }
+            }
+        });
+    }
+
+    // delete the selected entity, as long as it's something that's allowed to be deleted
+    fn delete_selected(&mut self) {
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            return;
+        };
+        if scene::delete(self.world, selected) {
+            **self.world.resource_mut::<Selected>() = None;
+        }
+    }
+
+    // copy the selected entity, and select the copy instead
+    fn duplicate_selected(&mut self) {
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            return;
+        };
+        if let Some(copy) = scene::duplicate(self.world, selected) {
+            **self.world.resource_mut::<Selected>() = Some(copy);
+        }
+    }
+
+    fn inspector_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            ui.label("No selection");
+            return;
+        };
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            inspector::entity_ui(ui, self.world, selected);
+        });
+    }
+
+    fn transform_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(selected) = **self.world.resource::<Selected>() else {
+            ui.label("No selection");
+            return;
+        };
+        inspector::transform_ui(ui, self.world, selected);
+    }
+
+    // how fast the viewport cameras move for a given drag or scroll
+    fn camera_controls_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = self.world.resource::<ViewportCameraSettings>();
+        let (mut orbit_speed, mut pan_speed, mut zoom_speed, mut invert_scroll) = (
+            settings.orbit_speed,
+            settings.pan_speed,
+            settings.zoom_speed,
+            settings.invert_scroll,
+        );
+        fn speed_slider<'a>(
+            value: &'a mut f32,
+            range: std::ops::RangeInclusive<f32>,
+            text: &str,
+        ) -> egui::Slider<'a> {
+            egui::Slider::new(value, range)
+                .logarithmic(true)
+                .fixed_decimals(4)
+                .text(text)
+        }
+        let mut changed = false;
+        changed |= ui
+            .add(speed_slider(&mut orbit_speed, 0.001..=0.1, "Orbit Speed"))
+            .changed();
+        changed |= ui
+            .add(speed_slider(&mut pan_speed, 0.0001..=0.01, "Pan Speed"))
+            .changed();
+        changed |= ui
+            .add(speed_slider(&mut zoom_speed, 0.005..=0.5, "Zoom Speed"))
+            .changed();
+        changed |= ui
+            .checkbox(&mut invert_scroll, "Invert Scroll")
+            .on_hover_text("Scroll up to zoom out rather than in")
+            .changed();
+        if ui.button("Reset Camera Speeds").clicked() {
+            let default = ViewportCameraSettings::default();
+            (orbit_speed, pan_speed, zoom_speed, invert_scroll) = (
+                default.orbit_speed,
+                default.pan_speed,
+                default.zoom_speed,
+                default.invert_scroll,
+            );
+            changed = true;
+        }
+        if changed {
+            let mut settings = self.world.resource_mut::<ViewportCameraSettings>();
+            settings.orbit_speed = orbit_speed;
+            settings.pan_speed = pan_speed;
+            settings.zoom_speed = zoom_speed;
+            settings.invert_scroll = invert_scroll;
+        }
+    }
+
+    // how close and far away things can be from the camera of the viewport used last and still be
+    // drawn. pushing the near plane out helps with z-fighting on big scenes, and pulling it in
+    // stops tiny models getting clipped. bevy's projection goes on forever, so the far plane just
+    // culls whatever's beyond it
+    fn clip_planes_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(camera) = self.world.resource::<ViewportInput>().camera else {
+            ui.label("No viewport has been used yet");
+            return;
+        };
+        let Some(Projection::Perspective(perspective)) = self.world.get::<Projection>(camera)
+        else {
+            ui.label("Only perspective cameras have adjustable clip planes");
+            return;
+        };
+        let (mut near, mut far) = (perspective.near, perspective.far);
+        egui::Grid::new("clip_planes")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Near");
+                ui.add(
+                    egui::DragValue::new(&mut near)
+                        .speed(0.01)
+                        .clamp_range(MIN_NEAR_PLANE..=f32::MAX),
+                );
+                ui.end_row();
+                ui.label("Far");
+                ui.add(egui::DragValue::new(&mut far).speed(1.));
+                ui.end_row();
+            });
+        if ui.button("Reset Clip Planes").clicked() {
+            let default = PerspectiveProjection::default();
+            (near, far) = (default.near, default.far);
+        }
+        // the far plane always has to be beyond the near one
+        near = near.max(MIN_NEAR_PLANE);
+        far = far.max(near * 2.);
+
+        // only write back if something changed, so the camera isn't recalculated every frame
+        if (near, far) != (perspective.near, perspective.far) {
+            if let Some(mut projection) = self.world.get_mut::<Projection>(camera) {
+                if let Projection::Perspective(perspective) = &mut *projection {
+                    perspective.near = near;
+                    perspective.far = far;
+                }
+            }
+        }
+    }
+
+    // save the view from whichever viewport was used last, and jump back to saved ones
+    fn bookmarks_ui(&mut self, ui: &mut egui::Ui) {
+        let camera = self.world.resource::<ViewportInput>().camera;
+        let transform = camera.and_then(|camera| self.world.get::<Transform>(camera).copied());
+        if ui
+            .add_enabled(transform.is_some(), egui::Button::new("Save Current View"))
+            .on_disabled_hover_text("No viewport has been used yet")
+            .clicked()
+        {
+            if let Some(transform) = transform {
+                self.world.resource_mut::<CameraBookmarks>().push(transform);
+            }
+        }
+        ui.separator();
+
+        let count = self.world.resource::<CameraBookmarks>().len();
+        if count == 0 {
+            ui.label("No saved views");
+        }
+        let mut removed = None;
+        for index in 0..count {
+            ui.horizontal(|ui| {
+                if index < BOOKMARK_KEYS.len() {
+                    ui.label(format!("View {} (Alt+{})", index + 1, index + 1));
+                } else {
+                    ui.label(format!("View {}", index + 1));
+                }
+                if ui
+                    .add_enabled(camera.is_some(), egui::Button::new("Go To"))
+                    .clicked()
+                {
+                    self.world.resource_mut::<ViewportInput>().bookmark = Some(index);
+                }
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.world.resource_mut::<CameraBookmarks>().remove(index);
+        }
+    }
+
+    // settings for the camera of whichever viewport was used last
+    fn camera_ui(&mut self, ui: &mut egui::Ui) {
+        // edit a copy, so the settings only get flagged as changed when they actually are
+        let mut smooth_moves = self.world.resource::<ViewportCameraSettings>().smooth_moves;
+        if ui
+            .checkbox(&mut smooth_moves, "Smooth Camera Moves")
+            .on_hover_text(
+                "Animate the camera when focusing or switching view, rather than jumping",
+            )
+            .changed()
+        {
+            self.world
+                .resource_mut::<ViewportCameraSettings>()
+                .smooth_moves = smooth_moves;
+        }
+
+        let Some(camera) = self.world.resource::<ViewportInput>().camera else {
+            ui.label("No viewport has been used yet");
+            return;
+        };
+        let (Some(transform), Some(pivot), Some(projection)) = (
+            self.world.get::<Transform>(camera).copied(),
+            self.world.get::<CameraPivot>(camera).map(|pivot| **pivot),
+            self.world.get::<Projection>(camera).cloned(),
+        ) else {
+            return;
+        };
+        let mut new_transform = transform;
+        let mut new_projection = projection.clone();
+        let mut changed = false;
+
+        let mut orthographic = matches!(projection, Projection::Orthographic(_));
+        if ui
+            .checkbox(&mut orthographic, "Orthographic")
+            .on_hover_text(
+                "Draw without perspective, so things stay the same size however far away",
+            )
+            .changed()
+        {
+            changed = true;
+            // keep whatever is at the pivot the same size on screen when switching
+            match projection {
+                Projection::Perspective(_) => {
+                    let distance = transform.translation.distance(pivot);
+                    new_projection = equivalent_orthographic(&projection, distance);
+                }
+                Projection::Orthographic(orthographic) => {
+                    let perspective = PerspectiveProjection::default();
+                    let distance = orthographic.scale / (perspective.fov / 2.).tan();
+                    new_transform.translation = pivot + transform.back() * distance;
+                    new_projection = Projection::Perspective(perspective);
+                }
+            }
+        }
+        match &mut new_projection {
+            Projection::Perspective(perspective) => {
+                let mut fov = perspective.fov.to_degrees();
+                if ui
+                    .add(
+                        egui::Slider::new(&mut fov, 10.0..=120.0)
+                            .text("FOV")
+                            .suffix("°"),
+                    )
+                    .on_hover_text("How wide the camera's view is, from top to bottom")
+                    .changed()
+                {
+                    perspective.fov = fov.to_radians();
+                    changed = true;
+                }
+            }
+            Projection::Orthographic(orthographic) => {
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut orthographic.scale, 0.1..=100.0)
+                            .logarithmic(true)
+                            .text("Scale"),
+                    )
+                    .on_hover_text("How much of the scene fits in the view")
+                    .changed();
+            }
+        }
+
+        // only write back if something changed, so the camera isn't recalculated every frame
+        if changed {
+            if let Some(mut projection) = self.world.get_mut::<Projection>(camera) {
+                *projection = new_projection;
+            }
+            if let Some(mut transform) = self.world.get_mut::<Transform>(camera) {
+                *transform = new_transform;
+            }
+        }
+    }
+
+    fn stats_ui(&mut self, ui: &mut egui::Ui) {
+        match self.frame_stats {
+            Some((fps, frame_time)) => {
+                ui.label(format!("FPS: {fps:.1}"));
+                ui.label(format!("Frame Time: {frame_time:.2} ms"));
+            }
+            // the diagnostics need a few frames before they have anything to show
+            None => {
+                ui.label("Measuring frame time...");
+            }
+        }
+        ui.separator();
+        ui.label(format!("Entities: {}", self.world.entities().len()));
+        ui.label(format!(
+            "Meshes: {}",
+            self.world.resource::<Assets<Mesh>>().len()
+        ));
+        ui.label(format!(
+            "Materials: {}",
+            self.world.resource::<Assets<StandardMaterial>>().len()
+        ));
+    }
+
+    fn lighting_ui(&mut self, ui: &mut egui::Ui) {
+        let mut ambient = self.world.resource_mut::<AmbientLight>();
+        // edit copies, so the light only gets flagged as changed when it actually is
+        let mut color = ambient.color.as_rgba_f32();
+        let mut brightness = ambient.brightness;
+        ui.horizontal(|ui| {
+            ui.label("Ambient Color:");
+            if ui.color_edit_button_rgba_unmultiplied(&mut color).changed() {
+                ambient.color = color.into();
+            }
+        });
+        if ui
+            .add(egui::Slider::new(&mut brightness, 0.0..=1.0).text("Ambient Brightness"))
+            .changed()
+        {
+            ambient.brightness = brightness;
+        }
+        ui.separator();
+
+        let mut settings = self.world.resource_mut::<RenderSettings>();
+        let mut show_light_direction = settings.show_light_direction;
+        if ui
+            .checkbox(&mut show_light_direction, "Show Light Direction")
+            .changed()
+        {
+            settings.show_light_direction = show_light_direction;
+        }
+        // bigger shadow maps give sharper shadows, at the cost of more memory and time to render
+        let mut shadow_map = self.world.resource_mut::<DirectionalLightShadowMap>();
+        let mut size = shadow_map.size;
+        egui::ComboBox::from_label("Shadow Map Size")
+            .selected_text(size.to_string())
+            .show_ui(ui, |ui| {
+                for option in SHADOW_MAP_SIZES {
+                    ui.selectable_value(&mut size, option, option.to_string());
+                }
+            });
+        if size != shadow_map.size {
+            shadow_map.size = size;
+        }
+        let mut key_lights = self
+            .world
+            .query_filtered::<(&mut Transform, &mut DirectionalLight), With<KeyLight>>();
+        let Ok((mut transform, mut light)) = key_lights.get_single_mut(self.world) else {
+            ui.label("Add the KeyLight component to a directional light to edit it here");
+            return;
+        };
+
+        // the light is pointed around by the direction towards it, as an angle around from +z and
+        // an angle up from the horizon
+        let to_light = transform.back();
+        let mut yaw = to_light.x.atan2(to_light.z).to_degrees();
+        let mut pitch = to_light.y.clamp(-1., 1.).asin().to_degrees();
+        let yaw_changed = ui
+            .add(
+                egui::Slider::new(&mut yaw, -180.0..=180.0)
+                    .text("Light Yaw")
+                    .suffix("°"),
+            )
+            .changed();
+        let pitch_changed = ui
+            .add(
+                egui::Slider::new(&mut pitch, -MAX_LIGHT_PITCH..=MAX_LIGHT_PITCH)
+                    .text("Light Pitch")
+                    .suffix("°"),
+            )
+            .changed();
+        if yaw_changed || pitch_changed {
+            let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+            let to_light = Vec3::new(
+                pitch.cos() * yaw.sin(),
+                pitch.sin(),
+                pitch.cos() * yaw.cos(),
+            );
+            let translation = transform.translation;
+            transform.look_at(translation - to_light, Vec3::Y);
+        }
+
+        let mut illuminance = light.illuminance;
+        if ui
+            .add(
+                egui::Slider::new(&mut illuminance, 0.0..=200_000.0)
+                    .logarithmic(true)
+                    .text("Illuminance")
+                    .suffix(" lux"),
+            )
+            .changed()
+        {
+            light.illuminance = illuminance;
+        }
+        let mut shadows_enabled = light.shadows_enabled;
+        if ui.checkbox(&mut shadows_enabled, "Shadows").changed() {
+            light.shadows_enabled = shadows_enabled;
+        }
+    }
+
+    // draw an arrow in the corner of the viewport pointing the way the key light shines, as seen
+    // from the viewport's camera
+    fn light_direction_ui(&mut self, ui: &egui::Ui, rect: egui::Rect, camera: Entity) {
+        let Some(camera_transform) = self.world.get::<GlobalTransform>(camera).copied() else {
+            return;
+        };
+        let mut key_lights = self
+            .world
+            .query_filtered::<&GlobalTransform, (With<KeyLight>, With<DirectionalLight>)>();
+        let Ok(light_transform) = key_lights.get_single(self.world) else {
+            return;
+        };
+        let direction = camera_transform
+            .compute_matrix()
+            .inverse()
+            .transform_vector3(light_transform.forward());
+
+        let painter = ui.painter_at(rect);
+        let center = rect.right_bottom() - egui::vec2(LIGHT_ARROW_SIZE, LIGHT_ARROW_SIZE) * 1.5;
+        let color = egui::Color32::from_rgb(250, 220, 60);
+        painter.circle_stroke(
+            center,
+            LIGHT_ARROW_SIZE,
+            egui::Stroke::new(1., egui::Color32::from_white_alpha(100)),
+        );
+        // screen y is flipped, and the arrow gets shorter as the light points into or out of the
+        // screen
+        let arrow = egui::vec2(direction.x, -direction.y) * LIGHT_ARROW_SIZE;
+        painter.circle_filled(center - arrow, 4., color);
+        painter.arrow(center - arrow, arrow * 2., egui::Stroke::new(2., color));
+    }
+
+    // draw the world axes in the corner of the viewport, as seen from its camera. clicking the end of
+    // one returns the view looking back along it, for the camera to snap to
+    fn axes_ui(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        camera: Entity,
+    ) -> Option<ViewPreset> {
+        let world_to_view = self
+            .world
+            .get::<GlobalTransform>(camera)?
+            .compute_matrix()
+            .inverse();
+        let center = response.rect.right_top() + egui::vec2(-1., 1.) * AXES_SIZE * 1.5;
+        let (red, green, blue) = (
+            egui::Color32::from_rgb(230, 60, 60),
+            egui::Color32::from_rgb(60, 200, 60),
+            egui::Color32::from_rgb(60, 110, 240),
+        );
+        // the negative axes are left unlabelled
+        let mut ends: Vec<_> = [
+            (ViewPreset::Right, "X", red),
+            (ViewPreset::Top, "Y", green),
+            (ViewPreset::Front, "Z", blue),
+            (ViewPreset::Left, "", red),
+            (ViewPreset::Bottom, "", green),
+            (ViewPreset::Back, "", blue),
+        ]
+        .into_iter()
+        .map(|(preset, label, color)| {
+            let direction = world_to_view.transform_vector3(preset.direction());
+            let end = center + egui::vec2(direction.x, -direction.y) * AXES_SIZE;
+            (preset, label, color, end, direction.z)
+        })
+        .collect();
+        // draw the ends furthest from the camera first, so the nearer ones go on top
+        ends.sort_by(|(.., a), (.., b)| a.total_cmp(b));
+
+        let painter = ui.painter_at(response.rect);
+        for &(_, label, color, end, _) in &ends {
+            if label.is_empty() {
+                painter.circle_stroke(end, AXES_HANDLE_RADIUS * 0.6, egui::Stroke::new(1.5, color));
+            } else {
+                painter.line_segment([center, end], egui::Stroke::new(2., color));
+                painter.circle_filled(end, AXES_HANDLE_RADIUS, color);
+                painter.text(
+                    end,
+                    egui::Align2::CENTER_CENTER,
+                    label,
+                    egui::FontId::proportional(11.),
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+
+        if !response.clicked_by(egui::PointerButton::Primary) {
+            return None;
+        }
+        let pointer = response.interact_pointer_pos()?;
+        // the nearest end wins where they overlap, the same as they're drawn
+        ends.iter()
+            .rev()
+            .find(|(.., end, _)| end.distance(pointer) <= AXES_HANDLE_RADIUS)
+            .map(|(preset, ..)| *preset)
+    }
+
+    fn render_ui(&mut self, ui: &mut egui::Ui) {
+        let mut settings = self.world.resource_mut::<RenderSettings>();
+        // edit a copy, so the settings only get flagged as changed when they actually are
+        let mut clear_color = settings.clear_color.as_rgba_f32();
+        ui.horizontal(|ui| {
+            ui.label("Background:");
+            // keep the alpha, since the viewport image is drawn blended over the tab
+            if ui
+                .color_edit_button_rgba_unmultiplied(&mut clear_color)
+                .changed()
+            {
+                settings.clear_color = clear_color.into();
+            }
+        });
+        let mut show_frame_stats = settings.show_frame_stats;
+        if ui
+            .checkbox(&mut show_frame_stats, "Show Frame Stats")
+            .changed()
+        {
+            settings.show_frame_stats = show_frame_stats;
+        }
+
+        let mut hdr = settings.hdr;
+        let mut tonemapping = settings.tonemapping;
+        ui.checkbox(&mut hdr, "HDR");
+        egui::ComboBox::from_label("Tonemapping")
+            .selected_text(format!("{tonemapping:?}"))
+            .show_ui(ui, |ui| {
+                for option in [
+                    Tonemapping::None,
+                    Tonemapping::Reinhard,
+                    Tonemapping::ReinhardLuminance,
+                    Tonemapping::AcesFitted,
+                    Tonemapping::AgX,
+                    Tonemapping::SomewhatBoringDisplayTransform,
+                    Tonemapping::TonyMcMapface,
+                    Tonemapping::BlenderFilmic,
+                ] {
+                    ui.selectable_value(&mut tonemapping, option, format!("{option:?}"));
+                }
+            });
+        let mut bloom = settings.bloom;
+        let mut bloom_intensity = settings.bloom_intensity;
+        ui.add_enabled(hdr, egui::Checkbox::new(&mut bloom, "Bloom"))
+            .on_disabled_hover_text("Bloom needs HDR to be on");
+        ui.add_enabled(
+            hdr && bloom,
+            egui::Slider::new(&mut bloom_intensity, 0.0..=1.0).text("Bloom Intensity"),
+        );
+        if hdr != settings.hdr
+            || tonemapping != settings.tonemapping
+            || bloom != settings.bloom
+            || bloom_intensity != settings.bloom_intensity
+        {
+            settings.hdr = hdr;
+            settings.tonemapping = tonemapping;
+            settings.bloom = bloom;
+            settings.bloom_intensity = bloom_intensity;
+        }
+
+        // msaa is applied to everything bevy renders rather than per camera. the camera renders into
+        // its own multisampled texture and resolves it into the viewport image, so the image itself
+        // stays single sampled and egui can display it as normal
+        let mut msaa = *self.world.resource::<Msaa>();
+        let msaa_name = |msaa: Msaa| match msaa {
+            Msaa::Off => "Off".to_owned(),
+            msaa => format!("{}x", msaa.samples()),
+        };
+        egui::ComboBox::from_label("MSAA")
+            .selected_text(msaa_name(msaa))
+            .show_ui(ui, |ui| {
+                // not every gpu supports 2x and 8x, but 4x is always available
+                for option in [Msaa::Off, Msaa::Sample2, Msaa::Sample4, Msaa::Sample8] {
+                    ui.selectable_value(&mut msaa, option, msaa_name(option));
+                }
+            });
+        if msaa != *self.world.resource::<Msaa>() {
+            *self.world.resource_mut::<Msaa>() = msaa;
+        }
+
+        let mut viewport_config = self.world.resource_mut::<ViewportConfig>();
+        let mut render_scale = viewport_config.render_scale;
+        if ui
+            .add(egui::Slider::new(&mut render_scale, 0.5..=2.0).text("Render Scale"))
+            .changed()
+        {
+            viewport_config.render_scale = render_scale;
+        }
+
+        let mut wireframe = **self.world.resource::<WireframeState>();
+        if ui.checkbox(&mut wireframe, "Wireframe").changed() {
+            **self.world.resource_mut::<WireframeState>() = wireframe;
+        }
+        let mut show_world_axes = **self.world.resource::<ShowWorldAxes>();
+        if ui
+            .checkbox(&mut show_world_axes, "Show World Axes")
+            .changed()
+        {
+            **self.world.resource_mut::<ShowWorldAxes>() = show_world_axes;
+        }
+        minimap::minimap_settings_ui(ui, self.world);
+
+        ui.separator();
+        environment::environment_ui(ui, self.world);
+
+        ui.separator();
+        let mut settings = self.world.resource_mut::<RenderSettings>();
+        // as with the rest, only write the fog back when it's actually changed
+        let mut fog = settings.fog;
+        let mut fog_color = fog.color.as_rgba_f32();
+        ui.checkbox(&mut fog.enabled, "Fog");
+        ui.add_enabled_ui(fog.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Fog Color:");
+                ui.color_edit_button_rgba_unmultiplied(&mut fog_color);
+            });
+            egui::ComboBox::from_label("Fog Falloff")
+                .selected_text(format!("{:?}", fog.falloff))
+                .show_ui(ui, |ui| {
+                    for mode in [FogMode::Linear, FogMode::Exponential] {
+                        ui.selectable_value(&mut fog.falloff, mode, format!("{mode:?}"));
+                    }
+                });
+            match fog.falloff {
+                FogMode::Linear => {
+                    ui.add(egui::Slider::new(&mut fog.start, 0.0..=fog.end).text("Fog Start"));
+                    ui.add(
+                        egui::Slider::new(&mut fog.end, fog.start..=1000.0)
+                            .logarithmic(true)
+                            .text("Fog End"),
+                    );
+                }
+                FogMode::Exponential => {
+                    ui.add(
+                        egui::Slider::new(&mut fog.density, 0.001..=1.0)
+                            .logarithmic(true)
+                            .text("Fog Density"),
+                    );
+                }
+            }
+        });
+        if fog_color != fog.color.as_rgba_f32() {
+            fog.color = fog_color.into();
+        }
+        if fog != settings.fog {
+            settings.fog = fog;
+        }
+
+        ui.separator();
+        let mut outline = self.world.resource_mut::<OutlineSettings>();
+        // as with the grid below, the outline is only rebuilt when something actually changes
+        let (mut visible, mut width) = (outline.visible, outline.width);
+        let mut outline_color = outline.color.as_rgba_f32();
+        ui.checkbox(&mut visible, "Show Selection Outline");
+        ui.horizontal(|ui| {
+            ui.label("Outline Color:");
+            ui.color_edit_button_rgba_unmultiplied(&mut outline_color);
+        });
+        ui.add(egui::Slider::new(&mut width, 0.01..=0.2).text("Outline Width"));
+        if visible != outline.visible
+            || width != outline.width
+            || outline_color != outline.color.as_rgba_f32()
+        {
+            outline.visible = visible;
+            outline.width = width;
+            outline.color = outline_color.into();
+        }
+        let mut settings = self.world.resource_mut::<RenderSettings>();
+        let mut show_bounds = settings.show_bounds;
+        let mut bounds_color = settings.bounds_color.as_rgba_f32();
+        ui.checkbox(&mut show_bounds, "Show Bounding Box");
+        ui.horizontal(|ui| {
+            ui.label("Bounding Box Color:");
+            ui.color_edit_button_rgba_unmultiplied(&mut bounds_color);
+        });
+        if show_bounds != settings.show_bounds
+            || bounds_color != settings.bounds_color.as_rgba_f32()
+        {
+            settings.show_bounds = show_bounds;
+            settings.bounds_color = bounds_color.into();
+        }
+
+        ui.separator();
+        let mut grid = self.world.resource_mut::<GridSettings>();
+        // as above, only write back what actually changed so the grid isn't rebuilt every frame
+        let (mut visible, mut spacing) = (grid.visible, grid.spacing);
+        let mut grid_color = grid.color.as_rgba_f32();
+        ui.checkbox(&mut visible, "Show Grid");
+        ui.add(
+            egui::Slider::new(&mut spacing, 0.1..=10.0)
+                .logarithmic(true)
+                .text("Grid Spacing"),
+        );
+        ui.horizontal(|ui| {
+            ui.label("Grid Color:");
+            ui.color_edit_button_rgba_unmultiplied(&mut grid_color);
+        });
+        if visible != grid.visible || spacing != grid.spacing {
+            grid.visible = visible;
+            grid.spacing = spacing;
+        }
+        if grid_color != grid.color.as_rgba_f32() {
+            grid.color = grid_color.into();
+        }
+
+        ui.separator();
+        // edited as a copy too, so that the snap settings aren't flagged as changed every frame
+        let mut snap = *self.world.resource::<SnapSettings>();
+        ui.checkbox(&mut snap.enabled, "Snap");
+        ui.add_enabled_ui(snap.enabled, |ui| {
+            ui.add(
+                egui::DragValue::new(&mut snap.translate)
+                    .speed(0.05)
+                    .clamp_range(0.01..=100.0)
+                    .prefix("Translate: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut snap.rotate)
+                    .speed(0.5)
+                    .clamp_range(1.0..=180.0)
+                    .prefix("Rotate: ")
+                    .suffix("°"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut snap.scale)
+                    .speed(0.01)
+                    .clamp_range(0.01..=10.0)
+                    .prefix("Scale: "),
+            );
+        });
+        if snap != *self.world.resource::<SnapSettings>() {
+            *self.world.resource_mut::<SnapSettings>() = snap;
+        }
+    }
+}
+
+// ctrl+d (or cmd+d on a mac)
+fn duplicate_shortcut(input: &egui::InputState) -> bool {
+    input.modifiers.command && input.key_pressed(egui::Key::D)
+}
+
+// the srgb hex for a color, like #ff8000, with the alpha on the end only if it isn't opaque
+fn color_hex(color: Color) -> String {
+    let [r, g, b, a] = color.as_rgba_u8();
+    if a == u8::MAX {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+// the emissive color for a color picked in the ui and an intensity multiplying it. with no intensity
+// there's no emission at all, whatever the color, so nothing glows by accident
+fn emissive(color: [f32; 3], intensity: f32) -> Color {
+    if intensity > 0. {
+        Color::rgb(color[0], color[1], color[2]).as_rgba_linear() * intensity
+    } else {
+        Color::BLACK
+    }
+}
+
+// the opposite of emissive, splitting it back up into a color and an intensity
+fn split_emissive(emissive: Color) -> ([f32; 3], f32) {
+    let [r, g, b, _] = emissive.as_linear_rgba_f32();
+    let intensity = r.max(g).max(b);
+    if intensity <= 0. {
+        return ([0., 0., 0.], 0.);
+    }
+    let [r, g, b, _] = Color::rgb_linear(r / intensity, g / intensity, b / intensity).as_rgba_f32();
+    ([r, g, b], intensity)
+}
+
+// below this many pixels across, the viewport tab is effectively collapsed, so there's no point in
+// resizing the render target to match (and a zero-sized texture would be invalid anyway)
+const MIN_VIEWPORT_SIZE: u32 = 8;
+
+// the size in physical pixels that the viewport image should be to fill the given logical size,
+// or None if the viewport is too small to be worth resizing to
+fn viewport_target_size(viewport_size: Vec2, scale_factor: f64) -> Option<UVec2> {
+    // round rather than truncate so fractional scales (e.g. 1.5) aren't under-resolved
+    let size = (viewport_size * scale_factor as f32).round().as_uvec2();
+    (size.min_element() >= MIN_VIEWPORT_SIZE).then_some(size)
+}
+
+fn setup_docktree(
+    mut commands: Commands,
+    settings: Res<DockingViewportSettings>,
+    tab_registry: Res<TabRegistry>,
+) {
+    // use the layout from last time if there is one, otherwise start with the default
+    let saved = if settings.persistent {
+        load_layout()
+    } else {
+        None
+    };
+    let tree = saved
+        .and_then(|tree| repair_layout(tree, &tab_registry))
+        .unwrap_or_else(|| settings.layout.clone());
+
+    commands.insert_resource(DockTree(tree));
+}
+
+// the app might have stopped registering some of the tabs in a saved layout since it was saved, or
+// started registering them again, so swap those for placeholders (or back). it also makes sure
+// there's a viewport, and gives up on the layout altogether if there's nothing left in it
+fn repair_layout(mut tree: Tree<Tab>, tab_registry: &TabRegistry) -> Option<Tree<Tab>> {
+    for tab in tree.iter_mut().filter_map(|node| node.tabs_mut()).flatten() {
+        match tab {
+            Tab::Custom(name) if tab_registry.get(name).is_none() => {
+                info!("The {name} tab in the saved layout isn't registered, so it's been replaced");
+                *tab = Tab::Missing(std::mem::take(name));
+            }
+            Tab::Missing(name) if tab_registry.get(name).is_some() => {
+                *tab = Tab::Custom(std::mem::take(name));
+            }
+            _ => {}
+        }
+    }
+    if tree
+        .iter()
+        .filter_map(|node| node.tabs())
+        .all(|tabs| tabs.is_empty())
+    {
+        warn!("The saved layout has no tabs in it, using the default layout");
+        return None;
+    }
+    if viewport_ids(&tree).next().is_none() {
+        info!("The saved layout has no viewport, so one has been added");
+        tree.push_to_first_leaf(Tab::Viewport(ViewportId(0)));
+    }
+    Some(tree)
+}
+
+// the layout the app starts with the first time it is run, unless the plugin is given another
+pub fn default_layout() -> Tree<Tab> {
+    // create the docktree
+    let mut tree = Tree::new(vec![
+        Tab::Viewport(ViewportId(0)),
+        Tab::Custom("Tab 1".to_owned()),
+    ]);
+    // you can modify the tree before constructing the dock
+    let [a, b] = tree.split_left(
+        NodeIndex::root(),
+        0.3,
+        vec![
+            Tab::SceneControl,
+            Tab::Inspector,
+            Tab::Transform,
+            Tab::Render,
+            Tab::Lighting,
+        ],
+    );
+    let [_, _] = tree.split_below(
+        a,
+        0.7,
+        vec![Tab::Hierarchy, Tab::Custom("Tab 2".to_owned())],
+    );
+    let [_, _] = tree.split_below(b, 0.5, vec![Tab::Custom("Tab 3".to_owned())]);
+    tree
+}
+
+// where the dock layout gets saved to between runs
+const LAYOUT_PATH: &str = "layout.ron";
+
+// bump this whenever a change to the tabs (or anything else in the layout) means older layouts need
+// migrating to load properly, and add a step for it to migrate_layout
+const LAYOUT_VERSION: u32 = 1;
+
+// the layout as it's saved, along with which version of the app's layout it is
+#[derive(Serialize, Deserialize)]
+struct SavedLayout {
+    version: u32,
+    tree: Tree<Tab>,
+}
+
+// bring a layout saved by an older version of the app up to date, one version at a time. layouts
+// from newer versions can't be made sense of, so they give None
+fn migrate_layout(mut layout: SavedLayout) -> Option<Tree<Tab>> {
+    if layout.version > LAYOUT_VERSION {
+        return None;
+    }
+    while layout.version < LAYOUT_VERSION {
+        // the changes needed to get from each version to the next go here. version 0 layouts are
+        // the bare trees from before there was a wrapper. the oldest of those have a viewport tab
+        // without an id, but parse_layout has already read that as the first viewport, so
+        // there's nothing else to change for them
+        layout.version += 1;
+    }
+    Some(layout.tree)
+}
+
+fn parse_layout(layout: &str) -> Result<SavedLayout, ron::error::SpannedError> {
+    ron::from_str::<SavedLayout>(layout).or_else(|err| {
+        // layouts used to be saved as just the tree, which counts as version 0
+        let tree = ron::from_str(layout)
+            .ok()
+            .or_else(|| parse_legacy_tree(layout))
+            .ok_or(err)?;
+        Ok(SavedLayout { version: 0, tree })
+    })
+}
+
+// read a tree from before the viewport tab had an id, giving it the first viewport's
+fn parse_legacy_tree(layout: &str) -> Option<Tree<Tab>> {
+    let tree: Tree<LegacyTab> = ron::from_str(layout).ok()?;
+    // there's no way to map over the tabs in a tree, so it's written back out and read in again
+    // as regular tabs
+    ron::from_str(&ron::to_string(&tree).ok()?).ok()
+}
+
+// load the layout that was saved when the app last exited, if there is one
+fn load_layout() -> Option<Tree<Tab>> {
+    let layout = match std::fs::read_to_string(LAYOUT_PATH) {
+        Ok(layout) => layout,
+        // there won't be one the first time the app is run, so this isn't worth warning about
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            warn!("Could not read {LAYOUT_PATH}, using the default layout: {err}");
+            return None;
+        }
+    };
+    let layout = parse_layout(&layout)
+        .map_err(|err| warn!("Could not parse {LAYOUT_PATH}, using the default layout: {err}"))
+        .ok()?;
+    let version = layout.version;
+    let tree = migrate_layout(layout);
+    if tree.is_none() {
+        warn!(
+            "{LAYOUT_PATH} was saved by a newer version of the app (layout version {version}), \
+             using the default layout"
+        );
+    }
+    tree
+}
+
+// the layout as it gets written to LAYOUT_PATH, for parse_layout to read back
+fn serialize_layout(tree: &Tree<Tab>) -> ron::Result<String> {
+    let layout = SavedLayout {
+        version: LAYOUT_VERSION,
+        tree: tree.clone(),
+    };
+    ron::ser::to_string_pretty(&layout, default())
+}
+
+// save the layout when the app exits, so that it can be restored next time
+fn save_layout(
+    mut exit_events: EventReader<AppExit>,
+    docktree: Res<DockTree>,
+    maximized: Res<MaximizedTab>,
+) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    // a maximized tab is only temporary, so save the layout it'll go back to
+    let layout = match serialize_layout(maximized.as_ref().unwrap_or(&docktree.0)) {
+        Ok(layout) => layout,
+        Err(err) => {
+            warn!("Could not serialize the layout: {err}");
+            return;
+        }
+    };
+    match std::fs::write(LAYOUT_PATH, layout) {
+        Ok(()) => info!("Saved the layout to {LAYOUT_PATH}"),
+        Err(err) => warn!("Could not save the layout to {LAYOUT_PATH}: {err}"),
+    }
+}
+
+// where the named layouts get saved to between runs
+const LAYOUT_PRESETS_PATH: &str = "layouts.ron";
+
+// load the named layouts, fixing them up the same way as the layout from last time
+fn load_layout_presets(mut presets: ResMut<LayoutPresets>, tab_registry: Res<TabRegistry>) {
+    let saved = match std::fs::read_to_string(LAYOUT_PRESETS_PATH) {
+        Ok(saved) => saved,
+        // there won't be any until a layout has been saved, so this isn't worth warning about
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("Could not read {LAYOUT_PRESETS_PATH}: {err}");
+            return;
+        }
+    };
+    let saved: HashMap<String, SavedLayout> = match ron::from_str(&saved) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not parse {LAYOUT_PRESETS_PATH}: {err}");
+            return;
+        }
+    };
+    for (name, layout) in saved {
+        let version = layout.version;
+        match migrate_layout(layout).and_then(|tree| repair_layout(tree, &tab_registry)) {
+            Some(tree) => {
+                presets.insert(name, tree);
+            }
+            None => warn!(
+                "Could not load the {name} layout (layout version {version}) from \
+                 {LAYOUT_PRESETS_PATH}"
+            ),
+        }
+    }
+}
+
+// save the named layouts when the app exits, the same as the layout
+fn save_layout_presets(mut exit_events: EventReader<AppExit>, presets: Res<LayoutPresets>) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    let saved: HashMap<_, _> = presets
+        .iter()
+        .map(|(name, tree)| {
+            let layout = SavedLayout {
+                version: LAYOUT_VERSION,
+                tree: tree.clone(),
+            };
+            (name, layout)
+        })
+        .collect();
+    let saved = match ron::ser::to_string_pretty(&saved, default()) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not serialize the layouts: {err}");
+            return;
+        }
+    };
+    if let Err(err) = std::fs::write(LAYOUT_PRESETS_PATH, saved) {
+        warn!("Could not save the layouts to {LAYOUT_PRESETS_PATH}: {err}");
+    }
+}
+
+// where the ui preferences get saved to between runs
+const PREFERENCES_PATH: &str = "preferences.ron";
+
+// the ui preferences as they're saved. anything missing from an older file is left as it was
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SavedPreferences {
+    theme: UiTheme,
+    orbit_speed: f32,
+    pan_speed: f32,
+    zoom_speed: f32,
+    invert_scroll: bool,
+}
+
+impl Default for SavedPreferences {
+    fn default() -> Self {
+        let camera_settings = ViewportCameraSettings::default();
+        Self {
+            theme: default(),
+            orbit_speed: camera_settings.orbit_speed,
+            pan_speed: camera_settings.pan_speed,
+            zoom_speed: camera_settings.zoom_speed,
+            invert_scroll: camera_settings.invert_scroll,
+        }
+    }
+}
+
+// load the preferences that were saved when the app last exited, if there are any
+fn load_preferences(
+    mut theme: ResMut<UiTheme>,
+    mut camera_settings: ResMut<ViewportCameraSettings>,
+) {
+    let saved = match std::fs::read_to_string(PREFERENCES_PATH) {
+        Ok(saved) => saved,
+        // there won't be any the first time the app is run, so this isn't worth warning about
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("Could not read {PREFERENCES_PATH}: {err}");
+            return;
+        }
+    };
+    let saved: SavedPreferences = match ron::from_str(&saved) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not parse {PREFERENCES_PATH}: {err}");
+            return;
+        }
+    };
+    *theme = saved.theme;
+    camera_settings.orbit_speed = saved.orbit_speed;
+    camera_settings.pan_speed = saved.pan_speed;
+    camera_settings.zoom_speed = saved.zoom_speed;
+    camera_settings.invert_scroll = saved.invert_scroll;
+}
+
+// save the preferences when the app exits, the same as the layout
+fn save_preferences(
+    mut exit_events: EventReader<AppExit>,
+    theme: Res<UiTheme>,
+    camera_settings: Res<ViewportCameraSettings>,
+) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    let saved = SavedPreferences {
+        theme: *theme,
+        orbit_speed: camera_settings.orbit_speed,
+        pan_speed: camera_settings.pan_speed,
+        zoom_speed: camera_settings.zoom_speed,
+        invert_scroll: camera_settings.invert_scroll,
+    };
+    let saved = match ron::ser::to_string_pretty(&saved, default()) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not serialize the preferences: {err}");
+            return;
+        }
+    };
+    if let Err(err) = std::fs::write(PREFERENCES_PATH, saved) {
+        warn!("Could not save the preferences to {PREFERENCES_PATH}: {err}");
+    }
+}
+
+// where the camera bookmarks get saved to between runs
+const BOOKMARKS_PATH: &str = "bookmarks.ron";
+
+// a camera bookmark as it's saved. only the position and rotation matter for a camera
+#[derive(Serialize, Deserialize)]
+struct SavedBookmark {
+    translation: [f32; 3],
+    rotation: [f32; 4],
+}
+
+// load the bookmarks that were saved when the app last exited, if there are any
+fn load_bookmarks(mut bookmarks: ResMut<CameraBookmarks>) {
+    let saved = match std::fs::read_to_string(BOOKMARKS_PATH) {
+        Ok(saved) => saved,
+        // there won't be any until a view has been saved, so this isn't worth warning about
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            warn!("Could not read {BOOKMARKS_PATH}: {err}");
+            return;
+        }
+    };
+    let saved: Vec<SavedBookmark> = match ron::from_str(&saved) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not parse {BOOKMARKS_PATH}: {err}");
+            return;
+        }
+    };
+    bookmarks.extend(saved.into_iter().map(|bookmark| Transform {
+        translation: Vec3::from_array(bookmark.translation),
+        rotation: Quat::from_array(bookmark.rotation),
+        ..default()
+    }));
+}
+
+// save the bookmarks when the app exits, the same as the layout
+fn save_bookmarks(mut exit_events: EventReader<AppExit>, bookmarks: Res<CameraBookmarks>) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    let saved: Vec<_> = bookmarks
+        .iter()
+        .map(|transform| SavedBookmark {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+        })
+        .collect();
+    let saved = match ron::ser::to_string_pretty(&saved, default()) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Could not serialize the camera bookmarks: {err}");
+            return;
+        }
+    };
+    if let Err(err) = std::fs::write(BOOKMARKS_PATH, saved) {
+        warn!("Could not save the camera bookmarks to {BOOKMARKS_PATH}: {err}");
+    }
+}
+
+// the viewport images are rendered to by the cameras, drawn by egui (which needs to filter them),
+// and copied out of for screenshots, so their format needs to support all of that on this gpu
+fn check_viewport_format(
+    adapter: Option<Res<RenderAdapter>>,
+    mut config: ResMut<ViewportConfig>,
+    mut notifications: ResMut<Notifications>,
+) {
+    // there's no gpu to ask without the render plugin, so trust the config
+    let Some(adapter) = adapter else {
+        return;
+    };
+    let features = adapter.get_texture_format_features(config.format);
+    let usages =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+    if features.allowed_usages.contains(usages)
+        && features
+            .flags
+            .contains(TextureFormatFeatureFlags::FILTERABLE)
+    {
+        return;
+    }
+    let default = ViewportConfig::default().format;
+    warn!(
+        "The {:?} format can't be used for the viewports, using {default:?} instead",
+        config.format
+    );
+    notifications.error(format!(
+        "The {:?} format can't be used for the viewports",
+        config.format
+    ));
+    config.format = default;
+}
+
+// every viewport tab currently open in the docktree
+fn viewport_ids(tree: &Tree<Tab>) -> impl Iterator<Item = ViewportId> + '_ {
+    tree.iter()
+        .filter_map(|node| node.tabs())
+        .flatten()
+        .filter_map(|tab| match tab {
+            Tab::Viewport(id) => Some(*id),
+            _ => None,
+        })
+}
+
+// give every newly opened viewport tab its own camera and image, and get rid of those belonging to
+// viewport tabs that have been closed, so the gpu isn't doing work for nothing
+fn sync_viewports(
+    mut commands: Commands,
+    config: Res<ViewportConfig>,
+    docktree: Res<DockTree>,
+    maximized: Res<MaximizedTab>,
+    mut viewports: ResMut<Viewports>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_user_textures: ResMut<EguiUserTextures>,
+) {
+    // the viewports hidden by a maximized tab are kept, so their cameras are where they were left
+    let mut open: HashSet<ViewportId> = viewport_ids(&docktree).collect();
+    if let Some(stashed) = &**maximized {
+        open.extend(viewport_ids(stashed));
+    }
+    viewports.retain(|id, target| {
+        if open.contains(id) {
+            return true;
+        }
+        commands.entity(target.camera).despawn_recursive();
+        // the image is freed once its last handle (the one in the target) is dropped
+        egui_user_textures.remove_image(&target.image);
+        false
+    });
+    for id in open {
+        if !viewports.contains_key(&id) {
+            let target = spawn_viewport(
+                &mut commands,
+                &mut images,
+                &mut egui_user_textures,
+                id,
+                &config,
+            );
+            viewports.insert(id, target);
+        }
+    }
+}
+
+// the selection gets changed from all over the place (the viewports, the hierarchy, adding and
+// deleting things...), so like the tab events, this sends the event rather than each of them
+fn send_selection_events(
+    selected: Res<Selected>,
+    mut previous: Local<Option<Entity>>,
+    mut changed: EventWriter<SelectionChanged>,
+) {
+    if selected.is_changed() && **selected != *previous {
+        *previous = **selected;
+        changed.send(SelectionChanged(**selected));
+    }
+}
+
+// tabs get opened and closed from the window menu, their close buttons, switching layouts and so
+// on, so rather than sending the events from each of those, compare what's open to last frame
+fn send_tab_events(
+    docktree: Res<DockTree>,
+    mut open: Local<HashSet<String>>,
+    mut opened: EventWriter<TabOpened>,
+    mut closed: EventWriter<TabClosed>,
+) {
+    if !docktree.is_changed() {
+        return;
+    }
+    let now_open: HashSet<String> = docktree
+        .iter()
+        .filter_map(|node| node.tabs())
+        .flatten()
+        .map(|tab| tab.name().into_owned())
+        .collect();
+    for name in open.difference(&now_open) {
+        closed.send(TabClosed(name.clone()));
+    }
+    for name in now_open.difference(&open) {
+        opened.send(TabOpened(name.clone()));
+    }
+    *open = now_open;
+}
+
+fn spawn_viewport(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    egui_user_textures: &mut EguiUserTextures,
+    id: ViewportId,
+    config: &ViewportConfig,
+) -> ViewportTarget {
+    // initial size (will be immediately overwritten once the tab is shown)
+    let size = Extent3d {
+        width: config.default_size.x,
+        height: config.default_size.y,
+        ..default()
+    };
+
+    // this is the texture that will be rendered to
+    let mut image: Image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: config.format,
+            mip_level_count: 1,
+            sample_count: 1,
+            // COPY_SRC lets the image be copied back off the gpu for screenshots
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+
+    // fill image.data with zeroes
+    image.resize(size);
+
+    // create a handle to the image
+    let image_handle = images.add(image);
+    let texture_id = egui_user_textures.add_image(image_handle.clone());
+
+    // spawn a camera which renders to the image handle
+    let camera = commands
+        .spawn((
+            Camera3dBundle {
+                camera_3d: Camera3d::default(),
+                camera: Camera {
+                    // render to the image
+                    target: RenderTarget::Image(image_handle.clone()),
+                    ..default()
+                },
+                transform: Transform::from_translation(DEFAULT_CAMERA_POSITION)
+                    .looking_at(Vec3::ZERO, Vec3::Y),
+                ..default()
+            },
+            ViewportCamera,
+            CameraPivot::default(),
+            Name::new(format!("{} Camera", Tab::Viewport(id).name())),
+        ))
+        .id();
+
+    ViewportTarget {
+        image: image_handle,
+        texture_id,
+        camera,
+        requested_size: None,
+        requested_at: 0.,
+    }
+}
+
+// a window menu entry for a tab, which closes the tab when clicked if it's open and opens it if not
+fn toggle_tab(
+    ui: &mut egui::Ui,
+    docktree: &mut Tree<Tab>,
+    index: Option<(NodeIndex, TabIndex)>,
+    name: &str,
+    tab: impl FnOnce() -> Tab,
+) {
+    if ui.selectable_label(index.is_some(), name).clicked() {
+        match index {
+            Some(index) => {
+                docktree.remove_tab(index);
+            }
+            None => docktree.push_to_focused_leaf(tab()),
+        }
+    }
+}
+
+fn save_scene_file(world: &mut World) {
+    let path = world.resource::<SceneFileSettings>().path.clone();
+    scene::save_scene(world, &path);
+}
+
+fn open_scene_file(world: &mut World) {
+    let path = world.resource::<SceneFileSettings>().path.clone();
+    // the selection would be left pointing at a despawned entity otherwise
+    if scene::open_scene(world, &path) {
+        **world.resource_mut::<Selected>() = None;
+    }
+}
+
+// the classic four way split, with the top, front and right views locked in place in three of the
+// viewports and the first viewport left free in the top right
+fn open_quad_view(world: &mut World, docktree: &mut Tree<Tab>) {
+    let mut tree = Tree::new(vec![Tab::Viewport(ViewportId(1))]);
+    let [left, right] =
+        tree.split_right(NodeIndex::root(), 0.5, vec![Tab::Viewport(ViewportId(0))]);
+    tree.split_below(left, 0.5, vec![Tab::Viewport(ViewportId(2))]);
+    tree.split_below(right, 0.5, vec![Tab::Viewport(ViewportId(3))]);
+    *docktree = tree;
+    **world.resource_mut::<MaximizedTab>() = None;
+
+    let mut pending = world.resource_mut::<PendingViewLocks>();
+    pending.insert(ViewportId(1), ViewPreset::Top);
+    pending.insert(ViewportId(2), ViewPreset::Front);
+    pending.insert(ViewportId(3), ViewPreset::Right);
+    // the free viewport might have been locked before
+    if let Some(camera) = world
+        .resource::<Viewports>()
+        .get(&ViewportId(0))
+        .map(|target| target.camera)
+    {
+        world.entity_mut(camera).remove::<LockedView>();
+    }
+}
+
+// make the focused tab fill the whole dock area on its own, or put back the layout from before
+fn toggle_maximized(world: &mut World, docktree: &mut Tree<Tab>) {
+    let mut maximized = world.resource_mut::<MaximizedTab>();
+    if let Some(stashed) = maximized.take() {
+        *docktree = stashed;
+        return;
+    }
+    let Some((_, tab)) = docktree.find_active_focused() else {
+        return;
+    };
+    let tree = Tree::new(vec![tab.clone()]);
+    **maximized = Some(std::mem::replace(docktree, tree));
+}
+
+// switch to one of the named layouts, or save the current one as a new one
+fn layout_presets_ui(ui: &mut egui::Ui, world: &mut World, docktree: &mut Tree<Tab>) {
+    // sorted, since the order of a hashmap would jump around as layouts are added
+    let mut names: Vec<_> = world.resource::<LayoutPresets>().keys().cloned().collect();
+    names.sort();
+    if names.is_empty() {
+        ui.label("No saved layouts");
+    }
+    for name in names {
+        ui.horizontal(|ui| {
+            if ui.button(name.as_str()).clicked() {
+                if let Some(tree) = world.resource::<LayoutPresets>().get(&name).cloned() {
+                    *docktree = tree;
+                    **world.resource_mut::<MaximizedTab>() = None;
+                }
+                ui.close_menu();
+            }
+            if ui.small_button("Delete").clicked() {
+                world.resource_mut::<LayoutPresets>().remove(&name);
+                world
+                    .resource_mut::<Notifications>()
+                    .info(format!("Deleted the {name} layout"));
+            }
+        });
+    }
+    ui.separator();
+    let name_id = ui.id().with("layout_name");
+    let mut name = ui
+        .data(|data| data.get_temp::<String>(name_id))
+        .unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut name);
+        // saving under an existing name replaces that layout
+        if ui
+            .add_enabled(!name.is_empty(), egui::Button::new("Save Current As…"))
+            .clicked()
+        {
+            world
+                .resource_mut::<LayoutPresets>()
+                .insert(name.clone(), docktree.clone());
+            world
+                .resource_mut::<Notifications>()
+                .info(format!("Saved the layout as {name}"));
+            name.clear();
+            ui.close_menu();
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(name_id, name));
+}
+
+// the same as Tree::find_tab, but without having to make a custom tab to compare against
+fn find_custom_tab(tree: &Tree<Tab>, name: &str) -> Option<(NodeIndex, TabIndex)> {
+    tree.iter().enumerate().find_map(|(node_index, node)| {
+        let tab_index = node
+            .tabs()?
+            .iter()
+            .position(|tab| matches!(tab, Tab::Custom(custom) if custom == name))?;
+        Some((NodeIndex(node_index), TabIndex(tab_index)))
+    })
+}
+
+// this is an exclusive system so that the tabs can get at anything in the world
+fn update_ui(world: &mut World) {
+    // there's nowhere to draw the ui once the window has been closed, which happens for a frame or
+    // so before the app exits, so this isn't worth warning about
+    let Ok((egui_context, window)) = world
+        .query_filtered::<(&EguiContext, &Window), With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    // the egui context is reference counted, so cloning it lets us use it while the world is
+    // borrowed by the tabs
+    let mut egui_context = egui_context.clone();
+    let window_scale_factor = window.scale_factor();
+    let diagnostics = world.resource::<DiagnosticsStore>();
+    let smoothed = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+    let frame_stats = smoothed(FrameTimeDiagnosticsPlugin::FPS)
+        .zip(smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME));
+    let ctx = egui_context.get_mut();
+
+    // undo and redo work from anywhere, unless a text field is focused since it has its own undo.
+    // so do the other shortcuts, which are all there is while the menu bar is hidden
+    if !ctx.wants_keyboard_input() {
+        let (undo, redo) = ctx.input(|input| {
+            let pressed = input.modifiers.command && input.key_pressed(egui::Key::Z);
+            (
+                pressed && !input.modifiers.shift,
+                pressed && input.modifiers.shift,
+            )
+        });
+        if undo {
+            history::undo(world);
+        } else if redo {
+            history::redo(world);
+        }
+        let (save, open, toggle_menu_bar) = ctx.input(|input| {
+            (
+                input.modifiers.command && input.key_pressed(egui::Key::S),
+                input.modifiers.command && input.key_pressed(egui::Key::O),
+                input.key_pressed(egui::Key::F10),
+            )
+        });
+        if save {
+            save_scene_file(world);
+        } else if open {
+            open_scene_file(world);
+        }
+        if toggle_menu_bar {
+            let mut show_menu_bar = world.resource_mut::<ShowMenuBar>();
+            **show_menu_bar = !**show_menu_bar;
+        }
+    }
+
+    world.resource_scope(|world, mut docktree: Mut<DockTree>| {
+        // ctrl+space maximizes the focused tab, or puts the layout back, as does escape
+        if !ctx.wants_keyboard_input() {
+            let maximized = world.resource::<MaximizedTab>().is_some();
+            let (toggle, escape) = ctx.input(|input| {
+                (
+                    input.modifiers.command && input.key_pressed(egui::Key::Space),
+                    input.key_pressed(egui::Key::Escape),
+                )
+            });
+            if toggle || (maximized && escape) {
+                toggle_maximized(world, &mut docktree);
+            }
+        }
+
+        // the registry is taken out of the world while the tabs are shown, since the registered
+        // tabs need the world too
+        world.resource_scope(|world, tab_registry: Mut<TabRegistry>| {
+            let allow_closing_last_viewport =
+                world.resource::<DockSettings>().allow_closing_last_viewport;
+
+            // menu bar along the top of the screen
+            let show_menu_bar = **world.resource::<ShowMenuBar>();
+            egui::TopBottomPanel::top("top_panel").show_animated(ctx, show_menu_bar, |ui| {
+                egui::menu::bar(ui, |ui| {
+                    ui.menu_button("File", |ui| {
+                        if ui
+                            .add(egui::Button::new("Save Scene").shortcut_text("Ctrl+S"))
+                            .clicked()
+                        {
+                            save_scene_file(world);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add(egui::Button::new("Open Scene").shortcut_text("Ctrl+O"))
+                            .clicked()
+                        {
+                            open_scene_file(world);
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Edit", |ui| {
+                        let history = world.resource::<EditHistory>();
+                        let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
+                        if ui
+                            .add_enabled(
+                                can_undo,
+                                egui::Button::new("Undo").shortcut_text("Ctrl+Z"),
+                            )
+                            .clicked()
+                        {
+                            history::undo(world);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(
+                                can_redo,
+                                egui::Button::new("Redo").shortcut_text("Ctrl+Shift+Z"),
+                            )
+                            .clicked()
+                        {
+                            history::redo(world);
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Window", |ui| {
+                        // toggle each tab on or off
+                        for tab in &BUILT_IN_TABS {
+                            let index = docktree.find_tab(tab);
+                            toggle_tab(ui, &mut docktree, index, &tab.name(), || tab.clone());
+                        }
+                        // the registered tabs are looked up by name, so that a tab only needs to be
+                        // made for one when it's actually opened
+                        for name in tab_registry.names() {
+                            let index = find_custom_tab(&docktree, name);
+                            toggle_tab(ui, &mut docktree, index, name, || {
+                                Tab::Custom(name.to_owned())
+                            });
+                        }
+                        ui.separator();
+                        // any number of viewports can be open at once, so rather than toggling them
+                        // list the open ones (clicking one closes it) and allow opening another
+                        for id in viewport_ids(&docktree).collect::<Vec<_>>() {
+                            let tab = Tab::Viewport(id);
+                            let closeable =
+                                allow_closing_last_viewport || viewport_ids(&docktree).count() > 1;
+                            if ui
+                                .add_enabled(
+                                    closeable,
+                                    egui::SelectableLabel::new(true, tab.name().as_ref()),
+                                )
+                                .clicked()
+                            {
+                                if let Some(index) = docktree.find_tab(&tab) {
+                                    docktree.remove_tab(index);
+                                }
+                            }
+                        }
+                        if ui.button("New Viewport").clicked() {
+                            let id = viewport_ids(&docktree)
+                                .map(|ViewportId(id)| id + 1)
+                                .max()
+                                .unwrap_or(0);
+                            docktree.push_to_focused_leaf(Tab::Viewport(ViewportId(id)));
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui
+                            .add(egui::Button::new("Hide Menu Bar").shortcut_text("F10"))
+                            .clicked()
+                        {
+                            **world.resource_mut::<ShowMenuBar>() = false;
+                            ui.close_menu();
+                        }
+                        let maximized = world.resource::<MaximizedTab>().is_some();
+                        let label = if maximized {
+                            "Restore Layout"
+                        } else {
+                            "Maximize Tab"
+                        };
+                        if ui
+                            .add(egui::Button::new(label).shortcut_text("Ctrl+Space"))
+                            .clicked()
+                        {
+                            toggle_maximized(world, &mut docktree);
+                            ui.close_menu();
+                        }
+                        if ui.button("Quad View").clicked() {
+                            open_quad_view(world, &mut docktree);
+                            ui.close_menu();
+                        }
+                        // an escape hatch for if the saved layout has got into a mess
+                        if ui.button("Reset Layout").clicked() {
+                            docktree.0 = world.resource::<DockingViewportSettings>().layout.clone();
+                            // restoring a maximized tab would undo the reset otherwise
+                            **world.resource_mut::<MaximizedTab>() = None;
+                            world
+                                .resource_mut::<Notifications>()
+                                .info("Reset the layout");
+                            ui.close_menu();
+                        }
+                        ui.menu_button("Layouts", |ui| {
+                            layout_presets_ui(ui, world, &mut docktree);
+                        });
+                        ui.menu_button("Theme", |ui| {
+                            let theme = *world.resource::<UiTheme>();
+                            for option in UiTheme::ALL {
+                                if ui
+                                    .selectable_label(theme == option, option.name())
+                                    .clicked()
+                                {
+                                    if theme != option {
+                                        *world.resource_mut::<UiTheme>() = option;
+                                    }
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        ui.menu_button("Power Mode", |ui| {
+                            // only set it when another mode is picked, so the settings aren't
+                            // replaced every frame that the menu is open
+                            let power_mode = *world.resource::<PowerMode>();
+                            for mode in PowerMode::ALL {
+                                if ui
+                                    .selectable_label(power_mode == mode, mode.name())
+                                    .clicked()
+                                {
+                                    if power_mode != mode {
+                                        *world.resource_mut::<PowerMode>() = mode;
+                                    }
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.menu_button("Add", |ui| {
+                        for primitive in Primitive::ALL {
+                            if ui.button(primitive.name()).clicked() {
+                                // select it straight away, since it's probably about to be edited
+                                let entity = scene::spawn_primitive(world, primitive);
+                                **world.resource_mut::<Selected>() = Some(entity);
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.button("Point Light").clicked() {
+                            let entity = scene::spawn_point_light(world);
+                            **world.resource_mut::<Selected>() = Some(entity);
+                            ui.close_menu();
+                        }
+                        if ui.button("Spot Light").clicked() {
+                            let entity = scene::spawn_spot_light(world);
+                            **world.resource_mut::<Selected>() = Some(entity);
+                            ui.close_menu();
+                        }
+                    });
+                });
+            });
+
+            // this goes before the viewports reset whether they're hovered, so it sees last frame's
+            egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+                status_bar_ui(ui, world, frame_stats);
+            });
+
+            // the viewports will set this again if they're hovered
+            **world.resource_mut::<ViewportHovered>() = false;
+            // and the inspector will set this again if it's showing a material
+            **world.resource_mut::<scene::TextureDropTarget>() = None;
+            world.resource_mut::<ViewportInput>().hover = None;
+
+            // show the actual dock area
+            let viewports_closeable =
+                allow_closing_last_viewport || viewport_ids(&docktree).count() > 1;
+            DockArea::new(&mut docktree)
+                .show_close_buttons(true)
+                .style(Style::from_egui(ctx.style().as_ref()))
+                .show(
+                    ctx,
+                    &mut TabViewer {
+                        world,
+                        window_scale_factor,
+                        tab_registry: &tab_registry,
+                        viewports_closeable,
+                        frame_stats,
+                    },
+                );
+        });
+    });
+
+    let now = world.resource::<Time>().elapsed_seconds_f64();
+    notifications::notifications_ui(ctx, &mut world.resource_mut::<Notifications>(), now);
+}
+
+// a line along the bottom of the window with the gizmo mode, the selection, and the controls for
+// whatever the pointer is over
+fn status_bar_ui(ui: &mut egui::Ui, world: &mut World, frame_stats: Option<(f64, f64)>) {
+    let selected = **world.resource::<Selected>();
+    let selected_name = selected.map(|entity| {
+        world
+            .get::<Name>(entity)
+            .map_or_else(|| format!("Entity {entity:?}"), |name| name.to_string())
+    });
+    let hint = if **world.resource::<ViewportHovered>() {
+        let mut hint =
+            String::from("Drag: orbit · Middle drag: pan · Scroll: zoom · Click: select");
+        if selected.is_some() {
+            hint.push_str(" · W/E/R: gizmo · F: focus · Del: delete");
+        }
+        hint
+    } else {
+        String::from("Ctrl+Z: undo · Ctrl+Shift+Z: redo · Ctrl+Space: maximize tab")
+    };
+    // the menu bar can't bring itself back, so say how to
+    let hint = if **world.resource::<ShowMenuBar>() {
+        hint
+    } else {
+        format!("{hint} · F10: show menu bar")
+    };
+    ui.horizontal(|ui| {
+        ui.label(format!("Gizmo: {}", world.resource::<GizmoMode>().name()));
+        ui.separator();
+        ui.label(format!(
+            "Selected: {}",
+            selected_name.as_deref().unwrap_or("nothing")
+        ));
+        ui.separator();
+        ui.label(hint);
+        if let Some((fps, _)) = frame_stats {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(format!("{fps:.0} fps"));
+            });
+        }
+    });
+}
+
+// rotate the used viewport's camera around its pivot using the drag input from its tab
+fn orbit_camera(
+    mut viewport_input: ResMut<ViewportInput>,
+    viewport_hovered: Res<ViewportHovered>,
+    settings: Res<ViewportCameraSettings>,
+    mut camera: Query<(&mut Transform, &CameraPivot), (With<ViewportCamera>, Without<LockedView>)>,
+) {
+    let orbit = std::mem::take(&mut viewport_input.orbit);
+    if orbit == Vec2::ZERO || !**viewport_hovered {
+        return;
+    }
+    let Some(Ok((mut transform, pivot))) =
+        viewport_input.camera.map(|entity| camera.get_mut(entity))
+    else {
+        return;
+    };
+
+    let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    let yaw = yaw - orbit.x * settings.orbit_speed;
+    let pitch = (pitch - orbit.y * settings.orbit_speed).clamp(-MAX_PITCH, MAX_PITCH);
+    let distance = transform.translation.distance(**pivot);
+
+    // the camera looks down its local -Z, so placing it along its local +Z keeps the pivot in view
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    transform.translation = **pivot + transform.rotation * Vec3::Z * distance;
+}
+
+// move the used viewport's camera towards/away from its pivot using the scroll input from its tab
+fn zoom_camera(
+    mut viewport_input: ResMut<ViewportInput>,
+    viewport_hovered: Res<ViewportHovered>,
+    settings: Res<ViewportCameraSettings>,
+    mut camera: Query<(&mut Transform, &mut Projection, &CameraPivot), With<ViewportCamera>>,
+) {
+    let zoom = std::mem::take(&mut viewport_input.zoom);
+    if zoom == 0. || !**viewport_hovered {
+        return;
+    }
+    let Some(Ok((mut transform, mut projection, pivot))) =
+        viewport_input.camera.map(|entity| camera.get_mut(entity))
+    else {
+        return;
+    };
+
+    let zoom = if settings.invert_scroll { -zoom } else { zoom };
+    let distance = transform.translation.distance(**pivot);
+    let new_distance = (distance - zoom * settings.zoom_speed).max(settings.min_distance);
+    match projection.as_mut() {
+        Projection::Perspective(_) => {
+            let forward = transform.forward();
+            transform.translation += forward * (distance - new_distance);
+        }
+        // moving an orthographic camera closer doesn't make anything bigger, so shrink the visible
+        // area by the same amount instead
+        Projection::Orthographic(orthographic) => {
+            orthographic.scale *= new_distance / distance;
+        }
+    }
+}
+
+// how big a region to frame when there's nothing selected, or the selection has no bounding box
+const DEFAULT_FOCUS_RADIUS: f32 = 5.;
+// how far the world axes are drawn out from the origin
+const WORLD_AXES_LENGTH: f32 = 5.;
+// the closest the camera's near clip plane can be. any closer and the depth buffer loses too much
+// precision further away
+const MIN_NEAR_PLANE: f32 = 0.001;
+// how long the cones drawn at each spot light are
+const SPOT_CONE_LENGTH: f32 = 2.;
+// where the viewport cameras start, and go back to when there's nothing in the scene to frame
+const DEFAULT_CAMERA_POSITION: Vec3 = Vec3::new(20., 20., 20.);
+
+// move the used viewport's camera so the selected entity fills the view, looking at it from the same
+// direction as before
+fn focus_camera(
+    mut commands: Commands,
+    mut viewport_input: ResMut<ViewportInput>,
+    settings: Res<ViewportCameraSettings>,
+    selected: Res<Selected>,
+    mut camera: Query<(&mut Transform, &mut Projection, &mut CameraPivot), With<ViewportCamera>>,
+    targets: Query<(&GlobalTransform, Option<&Aabb>)>,
+) {
+    if !std::mem::take(&mut viewport_input.focus) {
+        return;
+    }
+    let Some(entity) = viewport_input.camera else {
+        return;
+    };
+    let Ok((mut transform, mut projection, mut pivot)) = camera.get_mut(entity) else {
+        return;
+    };
+
+    // fall back to the origin when nothing is selected
+    let (center, radius) = selected.and_then(|entity| targets.get(entity).ok()).map_or(
+        (Vec3::ZERO, DEFAULT_FOCUS_RADIUS),
+        |(global_transform, aabb)| {
+            match aabb {
+                // the sphere around the bounding box, after it has been scaled and rotated
+                Some(aabb) => (
+                    global_transform.transform_point(aabb.center.into()),
+                    global_transform
+                        .compute_matrix()
+                        .transform_vector3(aabb.half_extents.into())
+                        .length(),
+                ),
+                None => (global_transform.translation(), DEFAULT_FOCUS_RADIUS),
+            }
+        },
+    );
+
+    let distance = framing_distance(
+        projection.as_mut(),
+        radius,
+        transform.translation.distance(**pivot),
+    )
+    .max(settings.min_distance);
+
+    **pivot = center;
+    let target = Transform::from_translation(center + transform.back() * distance)
+        .looking_at(center, Vec3::Y);
+    move_camera(&mut commands, entity, &mut transform, target, &settings);
+}
+
+// how many frames framing the scene waits for meshes to get their bounds before leaving them out
+const MAX_BOUNDS_WAIT_FRAMES: u32 = 30;
+
+// move every viewport's camera to fit the whole scene in view, looking from the same direction as
+// before. it waits until everything has been spawned and had its bounds worked out, so loaded models
+// are included
+fn frame_scene(
+    mut commands: Commands,
+    mut frame_scene: ResMut<FrameScene>,
+    settings: Res<ViewportCameraSettings>,
+    mut cameras: Query<
+        (Entity, &mut Transform, &mut Projection, &mut CameraPivot),
+        With<ViewportCamera>,
+    >,
+    objects: Query<(Entity, Option<&Handle<Scene>>, Option<&SceneInstance>), With<SceneObject>>,
+    // everything under the scene objects, to find the meshes and their bounds
+    hierarchy: Query<
+        (
+            Option<&Children>,
+            Option<&Handle<Mesh>>,
+            Option<(&GlobalTransform, &Aabb)>,
+        ),
+        (Without<SelectionOutline>, Without<HoverOutline>),
+    >,
+    mut frames_waited: Local<u32>,
+) {
+    // the cameras are only spawned once the viewport tabs have been shown
+    if !**frame_scene || cameras.is_empty() {
+        return;
+    }
+    // gltf scenes don't get their meshes until they've finished loading
+    if objects
+        .iter()
+        .any(|(_, scene, instance)| scene.is_some() && instance.is_none())
+    {
+        return;
+    }
+
+    // the box around every mesh in the scene, including the ones further down the hierarchy
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    let mut remaining: Vec<Entity> = objects.iter().map(|(entity, ..)| entity).collect();
+    while let Some(entity) = remaining.pop() {
+        let Ok((children, mesh, bounds)) = hierarchy.get(entity) else {
+            continue;
+        };
+        if let Some(children) = children {
+            remaining.extend(children.iter());
+        }
+        match (mesh, bounds) {
+            // meshes only get their bounds at the end of the frame they're spawned in (or once
+            // they've loaded), but some never get any (like ones with NoFrustumCulling), so they're
+            // only waited for so long before being left out
+            (Some(_), None) => {
+                if *frames_waited < MAX_BOUNDS_WAIT_FRAMES {
+                    *frames_waited += 1;
+                    return;
+                }
+            }
+            (Some(_), Some((global_transform, aabb))) => {
+                let center = Vec3::from(aabb.center);
+                let half_extents = Vec3::from(aabb.half_extents);
+                for i in 0..8 {
+                    let sign = Vec3::new(
+                        if i & 1 == 0 { -1. } else { 1. },
+                        if i & 2 == 0 { -1. } else { 1. },
+                        if i & 4 == 0 { -1. } else { 1. },
+                    );
+                    let corner = global_transform.transform_point(center + sign * half_extents);
+                    min = min.min(corner);
+                    max = max.max(corner);
+                }
+            }
+            (None, _) => {}
+        }
+    }
+    **frame_scene = false;
+    *frames_waited = 0;
+
+    let bounds = min
+        .cmple(max)
+        .all()
+        .then(|| ((min + max) / 2., (max - min).length() / 2.));
+    for (entity, mut transform, mut projection, mut pivot) in &mut cameras {
+        let target = match bounds {
+            Some((center, radius)) => {
+                let distance = framing_distance(
+                    projection.as_mut(),
+                    radius,
+                    transform.translation.distance(**pivot),
+                )
+                .max(settings.min_distance);
+                **pivot = center;
+                Transform::from_translation(center + transform.back() * distance)
+                    .looking_at(center, Vec3::Y)
+            }
+            // there's nothing to frame, so go back to where the cameras start
+            None => {
+                **pivot = Vec3::ZERO;
+                Transform::from_translation(DEFAULT_CAMERA_POSITION).looking_at(Vec3::ZERO, Vec3::Y)
+            }
+        };
+        move_camera(&mut commands, entity, &mut transform, target, &settings);
+    }
+}
+
+// how far back a camera needs to be for a sphere of the given radius to fill its view. an
+// orthographic camera is zoomed to fit instead, so it stays the distance it already is
+fn framing_distance(projection: &mut Projection, radius: f32, distance: f32) -> f32 {
+    match projection {
+        Projection::Perspective(perspective) => radius / (perspective.fov / 2.).sin(),
+        Projection::Orthographic(orthographic) => {
+            orthographic.scale = radius;
+            distance
+        }
+    }
+}
+
+// move the used viewport's camera to one of the bookmarks. the pivot is kept the same distance in
+// front of the camera as it was, so orbiting afterwards still feels the same
+fn recall_bookmark(
+    mut commands: Commands,
+    mut viewport_input: ResMut<ViewportInput>,
+    bookmarks: Res<CameraBookmarks>,
+    settings: Res<ViewportCameraSettings>,
+    mut camera: Query<(&mut Transform, &mut CameraPivot), With<ViewportCamera>>,
+) {
+    let Some(index) = viewport_input.bookmark.take() else {
+        return;
+    };
+    let (Some(&target), Some(entity)) = (bookmarks.get(index), viewport_input.camera) else {
+        return;
+    };
+    let Ok((mut transform, mut pivot)) = camera.get_mut(entity) else {
+        return;
+    };
+
+    let distance = transform.translation.distance(**pivot);
+    **pivot = target.translation + target.forward() * distance;
+    move_camera(&mut commands, entity, &mut transform, target, &settings);
+}
+
+// snap the used viewport's camera to one of the axis aligned views, switching to orthographic since
+// that's what these views are for
+fn apply_view_preset(
+    mut commands: Commands,
+    mut viewport_input: ResMut<ViewportInput>,
+    settings: Res<ViewportCameraSettings>,
+    mut camera: Query<
+        (
+            &mut Transform,
+            &mut Projection,
+            &mut CameraPivot,
+            Option<&mut LockedView>,
+        ),
+        With<ViewportCamera>,
+    >,
+) {
+    let Some(preset) = viewport_input.view_preset.take() else {
+        return;
+    };
+    let Some(entity) = viewport_input.camera else {
+        return;
+    };
+    let Ok((mut transform, mut projection, mut pivot, locked)) = camera.get_mut(entity) else {
+        return;
+    };
+    // a locked camera stays locked, just to the new view
+    if let Some(mut locked) = locked {
+        locked.0 = preset;
+    }
+
+    let distance = transform.translation.distance(**pivot);
+    *projection = equivalent_orthographic(&projection, distance);
+    **pivot = Vec3::ZERO;
+    let target = Transform::from_translation(preset.direction() * distance)
+        .looking_at(Vec3::ZERO, preset.up());
+    move_camera(&mut commands, entity, &mut transform, target, &settings);
+}
+
+// point the cameras of any newly opened viewports along the views they're meant to be locked to.
+// the viewports only get their cameras a frame or so after being opened, so until then they wait
+fn apply_view_locks(
+    mut commands: Commands,
+    mut pending: ResMut<PendingViewLocks>,
+    viewports: Res<Viewports>,
+    mut cameras: Query<(&mut Transform, &mut Projection, &mut CameraPivot), With<ViewportCamera>>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    pending.retain(|id, &mut preset| {
+        let Some(target) = viewports.get(id) else {
+            return true;
+        };
+        let Ok((mut transform, mut projection, mut pivot)) = cameras.get_mut(target.camera) else {
+            return true;
+        };
+        let distance = DEFAULT_CAMERA_POSITION.length();
+        *projection = equivalent_orthographic(&projection, distance);
+        **pivot = Vec3::ZERO;
+        *transform = Transform::from_translation(preset.direction() * distance)
+            .looking_at(Vec3::ZERO, preset.up());
+        commands.entity(target.camera).insert(LockedView(preset));
+        false
+    });
+}
+
+// an orthographic projection which shows about as much of the scene around the pivot (at the given
+// distance away) as the current projection does
+fn equivalent_orthographic(projection: &Projection, distance: f32) -> Projection {
+    let scale = match projection {
+        Projection::Perspective(perspective) => distance * (perspective.fov / 2.).tan(),
+        Projection::Orthographic(orthographic) => orthographic.scale,
+    };
+    Projection::Orthographic(OrthographicProjection {
+        // with a fixed vertical size of 2, the scale is half the visible height
+        scale,
+        scaling_mode: ScalingMode::FixedVertical(2.),
+        ..default()
+    })
+}
+
+// jump the camera to the target, or start it gliding there if smooth moves are turned on
+fn move_camera(
+    commands: &mut Commands,
+    entity: Entity,
+    transform: &mut Transform,
+    target: Transform,
+    settings: &ViewportCameraSettings,
+) {
+    if settings.smooth_moves && settings.move_duration > 0. {
+        commands.entity(entity).insert(CameraTween {
+            start: *transform,
+            target,
+            duration: settings.move_duration,
+            elapsed: 0.,
+            last: *transform,
+        });
+    } else {
+        *transform = target;
+    }
+}
+
+fn tween_camera(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(Entity, &mut Transform, &mut CameraTween), With<ViewportCamera>>,
+) {
+    for (entity, mut transform, mut tween) in &mut cameras {
+        // the user has taken control of the camera, so stop fighting them
+        if *transform != tween.last {
+            commands.entity(entity).remove::<CameraTween>();
+            continue;
+        }
+        tween.elapsed += time.delta_seconds();
+        let t = (tween.elapsed / tween.duration).min(1.);
+        // ease in and out, so it doesn't start or stop abruptly
+        let t = t * t * (3. - 2. * t);
+        transform.translation = tween.start.translation.lerp(tween.target.translation, t);
+        transform.rotation = tween.start.rotation.slerp(tween.target.rotation, t);
+        transform.scale = tween.start.scale.lerp(tween.target.scale, t);
+        tween.last = *transform;
+
+        if tween.elapsed >= tween.duration {
+            commands.entity(entity).remove::<CameraTween>();
+        }
+    }
+}
+
+// slide the used viewport's camera (and its pivot) across its local right/up plane using the middle
+// mouse drag input from its tab
+fn pan_camera(
+    mut viewport_input: ResMut<ViewportInput>,
+    viewport_hovered: Res<ViewportHovered>,
+    settings: Res<ViewportCameraSettings>,
+    mut camera: Query<(&mut Transform, &mut CameraPivot), With<ViewportCamera>>,
+) {
+    let pan = std::mem::take(&mut viewport_input.pan);
+    if pan == Vec2::ZERO || !**viewport_hovered {
+        return;
+    }
+    let Some(Ok((mut transform, mut pivot))) =
+        viewport_input.camera.map(|entity| camera.get_mut(entity))
+    else {
+        return;
+    };
+
+    let distance = transform.translation.distance(**pivot);
+    // drag left moves the scene left, so the camera itself moves right (and likewise for up/down)
+    let offset =
+        (transform.left() * pan.x + transform.up() * pan.y) * settings.pan_speed * distance;
+    transform.translation += offset;
+    **pivot += offset;
+}
+
+// the meshes (and point and spot lights) which can be picked out with the pointer, which leaves out the
+// editor's own ones
+type PickableMeshes<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Aabb, &'static GlobalTransform),
+    (
+        Or<(With<Handle<Mesh>>, With<PointLight>, With<SpotLight>)>,
+        Without<GridMarker>,
+        Without<SelectionOutline>,
+        Without<HoverOutline>,
+    ),
+>;
+
+// select the closest mesh under the pointer when the viewport is clicked, or clear the selection if
+// there's nothing there
+fn pick_entity(
+    mut viewport_input: ResMut<ViewportInput>,
+    viewport_hovered: Res<ViewportHovered>,
+    mut selected: ResMut<Selected>,
+    camera: Query<(&Camera, &GlobalTransform), With<ViewportCamera>>,
+    meshes: PickableMeshes,
+    parents: Query<&Parent>,
+    scene_objects: Query<(), With<SceneObject>>,
+) {
+    let Some(click) = viewport_input.click.take() else {
+        return;
+    };
+    if !**viewport_hovered {
+        return;
+    }
+    let Some(Ok((camera, camera_transform))) =
+        viewport_input.camera.map(|entity| camera.get(entity))
+    else {
+        return;
+    };
+    **selected = hit_at(click, camera, camera_transform, &meshes)
+        .and_then(|hit| hit.entity)
+        .map(|entity| scene_object_root(entity, &parents, &scene_objects));
+}
+
+// the scene object an entity is part of, which for a mesh in a loaded gltf is some way up its
+// parents, so that it's the whole object that gets selected (and can be deleted or duplicated).
+// anything that isn't part of a scene object is left as it is
+fn scene_object_root(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    scene_objects: &Query<(), With<SceneObject>>,
+) -> Entity {
+    let mut current = entity;
+    while !scene_objects.contains(current) {
+        let Ok(parent) = parents.get(current) else {
+            return entity;
+        };
+        current = parent.get();
+    }
+    current
+}
+
+// keep track of what's under the pointer, so it can be highlighted before it's clicked
+fn hover_entity(
+    viewport_input: Res<ViewportInput>,
+    viewport_hovered: Res<ViewportHovered>,
+    mut hovered: ResMut<Hovered>,
+    mut pointer_position: ResMut<PointerWorldPosition>,
+    camera: Query<(&Camera, &GlobalTransform), With<ViewportCamera>>,
+    meshes: PickableMeshes,
+) {
+    // there's no point casting a ray when the pointer isn't over a viewport
+    let hit = match (viewport_input.hover, viewport_input.camera) {
+        (Some(hover), Some(camera_entity)) if **viewport_hovered => camera
+            .get(camera_entity)
+            .ok()
+            .and_then(|(camera, camera_transform)| {
+                hit_at(hover, camera, camera_transform, &meshes)
+            }),
+        _ => None,
+    };
+    let entity = hit.and_then(|hit| hit.entity);
+    // only touch it when it changes, since the hover outline is rebuilt whenever it's changed
+    if **hovered != entity {
+        **hovered = entity;
+    }
+    let position = hit.map(|hit| hit.position);
+    if **pointer_position != position {
+        **pointer_position = position;
+    }
+}
+
+// where a ray from the camera hit something. the entity is None if it only hit the ground
+#[derive(Clone, Copy)]
+struct Hit {
+    entity: Option<Entity>,
+    position: Vec3,
+}
+
+// the nearest mesh under a point in the viewport, given in normalized device coordinates, or the
+// ground plane if there isn't one
+fn hit_at(
+    ndc: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    meshes: &PickableMeshes,
+) -> Option<Hit> {
+    // cast a ray from the near plane to the far plane (bevy uses reversed z, so near is 1)
+    let near = camera.ndc_to_world(camera_transform, ndc.extend(1.))?;
+    let far = camera.ndc_to_world(camera_transform, ndc.extend(f32::EPSILON))?;
+    let direction = (far - near).normalize();
+
+    let mesh_hit = meshes
+        .iter()
+        .filter_map(|(entity, aabb, transform)| {
+            // test against the mesh's bounding box in its own local space
+            let world_to_local = transform.compute_matrix().inverse();
+            let origin = world_to_local.transform_point3(near);
+            let local_direction = world_to_local.transform_vector3(direction);
+            ray_aabb_intersection(origin, local_direction, aabb).map(|t| (entity, t))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    // the distance along the ray is the same in world space, since the transforms are affine
+    if let Some((entity, t)) = mesh_hit {
+        return Some(Hit {
+            entity: Some(entity),
+            position: near + direction * t,
+        });
+    }
+    let t = -near.y / direction.y;
+    (t.is_finite() && t >= 0.).then(|| Hit {
+        entity: None,
+        position: near + direction * t,
+    })
+}
+
+// how far along the ray it first hits the box, if it hits it at all. the local space ray direction
+// isn't normalized, but since every transform is affine the distance still orders correctly
+fn ray_aabb_intersection(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let inverse_direction = direction.recip();
+    let t1 = (Vec3::from(aabb.min()) - origin) * inverse_direction;
+    let t2 = (Vec3::from(aabb.max()) - origin) * inverse_direction;
+    let t_min = t1.min(t2).max_element().max(0.);
+    let t_max = t1.max(t2).min_element();
+    (t_max >= t_min).then_some(t_min)
+}
+
+// push the render settings onto the viewport cameras (and only those, so the window's own camera is
+// left alone) whenever they change, or a new viewport appears
+fn apply_render_settings(
+    mut commands: Commands,
+    settings: Res<RenderSettings>,
+    mut cameras: Query<(
+        Entity,
+        &mut Camera,
+        &mut Camera3d,
+        &mut Tonemapping,
+        Ref<ViewportCamera>,
+    )>,
+) {
+    for (entity, mut camera, mut camera_3d, mut tonemapping, marker) in &mut cameras {
+        if settings.is_changed() || marker.is_added() {
+            camera_3d.clear_color = ClearColorConfig::Custom(settings.clear_color);
+            // with hdr on, bevy renders into an intermediate Rgba16Float texture, then tonemaps and
+            // writes the result into the viewport image, which stays 8 bit srgb for egui
+            camera.hdr = settings.hdr;
+            *tonemapping = settings.tonemapping;
+            // bloom is done on the hdr texture before tonemapping, so it ends up in the viewport
+            // image along with everything else
+            if settings.hdr && settings.bloom {
+                commands.entity(entity).insert(BloomSettings {
+                    intensity: settings.bloom_intensity,
+                    ..default()
+                });
+            } else {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            if settings.fog.enabled {
+                commands.entity(entity).insert(settings.fog.settings());
+            } else {
+                commands.entity(entity).remove::<FogSettings>();
+            }
+        }
+    }
+}
+
+// give egui the app's fonts. bevy_egui makes the window's context before startup, and egui only
+// switches fonts at the start of a frame, so doing this at startup means even the first frame has
+// them. a font that can't be read is skipped, leaving egui's own
+fn load_fonts(fonts: Res<UiFonts>, mut egui_context: Query<&mut EguiContext, With<PrimaryWindow>>) {
+    if fonts.0.is_empty() {
+        return;
+    }
+    let Ok(mut egui_context) = egui_context.get_single_mut() else {
+        return;
+    };
+    let mut definitions = egui::FontDefinitions::default();
+    let mut loaded = 0;
+    for (name, source) in &fonts.0 {
+        let data = match source {
+            FontSource::Path(path) => match std::fs::read(path) {
+                Ok(bytes) => egui::FontData::from_owned(bytes),
+                Err(err) => {
+                    warn!(
+                        "Could not load the {name} font from {}: {err}",
+                        path.display()
+                    );
+                    continue;
+                }
+            },
+            FontSource::Bytes(bytes) => egui::FontData::from_static(bytes),
+        };
+        definitions.font_data.insert(name.clone(), data);
+        // before egui's fonts for normal text, but after them for monospace text, so that code and
+        // numbers still line up while the font can fill in characters egui doesn't have
+        if let Some(family) = definitions
+            .families
+            .get_mut(&egui::FontFamily::Proportional)
+        {
+            family.insert(loaded, name.clone());
+        }
+        if let Some(family) = definitions.families.get_mut(&egui::FontFamily::Monospace) {
+            family.push(name.clone());
+        }
+        loaded += 1;
+    }
+    if loaded > 0 {
+        egui_context.get_mut().set_fonts(definitions);
+    }
+}
+
+// set the theme on the window's egui context, which might not exist for the first frame or so
+fn apply_theme(
+    theme: Res<UiTheme>,
+    mut applied: Local<Option<UiTheme>>,
+    mut egui_context: Query<&mut EguiContext, With<PrimaryWindow>>,
+) {
+    if *applied == Some(*theme) {
+        return;
+    }
+    let Ok(mut egui_context) = egui_context.get_single_mut() else {
+        return;
+    };
+    egui_context.get_mut().set_visuals(theme.visuals());
+    *applied = Some(*theme);
+}
+
+// replace the WinitSettings to match the power mode, including when the app starts
+fn apply_power_mode(mut commands: Commands, power_mode: Res<PowerMode>) {
+    if power_mode.is_changed() {
+        commands.insert_resource(power_mode.winit_settings());
+    }
+}
+
+// draw the selected entity's bounding box with bevy's gizmos, which get redrawn every frame so the
+// box follows the entity around as it's moved
+fn draw_selection_bounds(
+    mut gizmos: Gizmos,
+    settings: Res<RenderSettings>,
+    selected: Res<Selected>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+) {
+    if !settings.show_bounds {
+        return;
+    }
+    let Some((global_transform, aabb)) = selected.and_then(|entity| bounds.get(entity).ok()) else {
+        return;
+    };
+    // the bounding box is in the entity's own space, so it gets rotated and scaled along with it
+    let bounds = Transform::from_translation(aabb.center.into())
+        .with_scale(Vec3::from(aabb.half_extents) * 2.);
+    gizmos.cuboid(*global_transform * bounds, settings.bounds_color);
+}
+
+// gizmos are drawn by every 3d camera, and the only ones are the viewport cameras (the window itself
+// is just egui), so these only show up in the viewports
+fn draw_world_axes(mut gizmos: Gizmos, show_world_axes: Res<ShowWorldAxes>) {
+    if !**show_world_axes {
+        return;
+    }
+    gizmos.line(Vec3::ZERO, Vec3::X * WORLD_AXES_LENGTH, Color::RED);
+    gizmos.line(Vec3::ZERO, Vec3::Y * WORLD_AXES_LENGTH, Color::GREEN);
+    gizmos.line(Vec3::ZERO, Vec3::Z * WORLD_AXES_LENGTH, Color::BLUE);
+}
+
+// point lights have nothing of their own to see them by, so draw a sphere in the light's color at
+// each one
+fn draw_point_lights(
+    mut gizmos: Gizmos,
+    lights: Query<(&GlobalTransform, &PointLight), With<SceneObject>>,
+) {
+    for (global_transform, light) in &lights {
+        gizmos.sphere(
+            global_transform.translation(),
+            Quat::IDENTITY,
+            scene::LIGHT_ICON_RADIUS,
+            light.color,
+        );
+    }
+}
+
+// draw each spot light's cone, with the outer angle (where the light ends) in the light's color and
+// the inner angle (where it starts to fade) fainter. the cones are drawn a fixed length rather than
+// out to the light's range, which would usually be far bigger than the rest of the scene
+fn draw_spot_lights(
+    mut gizmos: Gizmos,
+    lights: Query<(&GlobalTransform, &SpotLight), With<SceneObject>>,
+) {
+    for (global_transform, light) in &lights {
+        let apex = global_transform.translation();
+        let forward = global_transform.forward();
+        let center = apex + forward * SPOT_CONE_LENGTH;
+        let outer_radius = SPOT_CONE_LENGTH * light.outer_angle.tan();
+        gizmos.circle(center, forward, outer_radius, light.color);
+        for side in [
+            global_transform.right(),
+            global_transform.left(),
+            global_transform.up(),
+            global_transform.down(),
+        ] {
+            gizmos.line(apex, center + side * outer_radius, light.color);
+        }
+        if light.inner_angle > 0. {
+            let inner_radius = SPOT_CONE_LENGTH * light.inner_angle.tan();
+            gizmos.circle(center, forward, inner_radius, light.color.with_a(0.4));
+        }
+    }
+}
+
+// keep the Wireframe component on just the scene's meshes, including any added since the wireframe
+// was turned on
+fn apply_wireframe(
+    mut commands: Commands,
+    wireframe: Res<WireframeState>,
+    without_wireframe: Query<Entity, (With<SceneObject>, With<Handle<Mesh>>, Without<Wireframe>)>,
+    with_wireframe: Query<Entity, (With<SceneObject>, With<Wireframe>)>,
+) {
+    if **wireframe {
+        for entity in &without_wireframe {
+            commands.entity(entity).insert(Wireframe);
+        }
+    } else {
+        for entity in &with_wireframe {
+            commands.entity(entity).remove::<Wireframe>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    // draw a tab for a single frame of a bare egui context, with the tab viewer getting at the
+    // given world like it would in the app
+    fn run_tab_ui(world: &mut World, mut tab: Tab) {
+        let tab_registry = TabRegistry::default();
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let mut tab_viewer = TabViewer {
+                    world: &mut *world,
+                    window_scale_factor: 1.,
+                    tab_registry: &tab_registry,
+                    viewports_closeable: false,
+                    frame_stats: None,
+                };
+                egui_dock::TabViewer::ui(&mut tab_viewer, ui, &mut tab);
+            });
+        });
+    }
+
+    #[test]
+    fn selection_without_a_material() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<ViewportCameraSettings>();
+        world.init_resource::<ViewportInput>();
+        world.init_resource::<EditHistory>();
+        let entity = world
+            .spawn((Transform::default(), Name::new("No Material")))
+            .id();
+        world.insert_resource(Selected(Some(entity)));
+
+        run_tab_ui(&mut world, Tab::SceneControl);
+        run_tab_ui(&mut world, Tab::Inspector);
+        // the selection is left alone, there's just nothing to edit
+        assert_eq!(**world.resource::<Selected>(), Some(entity));
+    }
+
+    #[test]
+    fn removed_tabs_become_missing() {
+        let tabs = vec![
+            Tab::Viewport(ViewportId(1)),
+            Tab::Inspector,
+            Tab::Custom("Tab 1".to_owned()),
+            Tab::Missing("Tab 2".to_owned()),
+        ];
+        let saved = ron::to_string(&tabs).unwrap();
+        assert!(ron::from_str::<Vec<Tab>>(&saved).unwrap() == tabs);
+
+        let tabs: Vec<Tab> = ron::from_str("[Hierarchy, Outliner, Log]").unwrap();
+        assert!(
+            tabs == [
+                Tab::Hierarchy,
+                Tab::Missing("Outliner".to_owned()),
+                Tab::Log
+            ]
+        );
+    }
+
+    // every node in the tree in order, with the tabs in each leaf, so trees can be compared
+    fn describe_layout(tree: &Tree<Tab>) -> Vec<String> {
+        tree.iter()
+            .map(|node| match node {
+                egui_dock::Node::Empty => "Empty".to_owned(),
+                egui_dock::Node::Leaf { tabs, .. } => {
+                    let names: Vec<_> = tabs.iter().map(|tab| tab.name()).collect();
+                    format!("Leaf [{}]", names.join(", "))
+                }
+                egui_dock::Node::Horizontal { fraction, .. } => format!("Horizontal {fraction}"),
+                egui_dock::Node::Vertical { fraction, .. } => format!("Vertical {fraction}"),
+            })
+            .collect()
+    }
+
+    fn nested_layout() -> Tree<Tab> {
+        let mut tree = Tree::new(vec![Tab::Viewport(ViewportId(0)), Tab::Stats]);
+        let [viewport, _] = tree.split_right(
+            NodeIndex::root(),
+            0.6,
+            vec![
+                Tab::Viewport(ViewportId(1)),
+                Tab::Custom("Tab 1".to_owned()),
+            ],
+        );
+        let [_, below] = tree.split_below(viewport, 0.25, vec![Tab::Log, Tab::Camera]);
+        let [_, _] = tree.split_left(below, 0.5, vec![Tab::Custom("Tab 2".to_owned())]);
+        tree
+    }
+
+    #[test]
+    fn layouts_round_trip() {
+        for tree in [default_layout(), nested_layout()] {
+            let saved = ron::to_string(&SavedLayout {
+                version: LAYOUT_VERSION,
+                tree: tree.clone(),
+            })
+            .unwrap();
+            let layout = parse_layout(&saved).unwrap();
+            assert_eq!(layout.version, LAYOUT_VERSION);
+            assert_eq!(describe_layout(&layout.tree), describe_layout(&tree));
+        }
+        // the default layout is split into four leaves, with the viewport sharing one with tab 1
+        let default = describe_layout(&default_layout());
+        assert_eq!(
+            default
+                .iter()
+                .filter(|node| node.starts_with("Leaf"))
+                .count(),
+            4
+        );
+        assert!(default.contains(&"Leaf [Viewport, Tab 1]".to_owned()));
+    }
+
+    #[test]
+    fn saved_layouts_load_again() {
+        for tree in [default_layout(), nested_layout()] {
+            let saved = serialize_layout(&tree).unwrap();
+            let layout = parse_layout(&saved).unwrap();
+            assert_eq!(layout.version, LAYOUT_VERSION);
+            let loaded = migrate_layout(layout).unwrap();
+            assert_eq!(describe_layout(&loaded), describe_layout(&tree));
+        }
+    }
+
+    #[test]
+    fn repairing_layouts() {
+        let mut tab_registry = TabRegistry::default();
+        tab_registry.register("Tab 2", |_, _| {});
+        let mut tree = Tree::new(vec![Tab::Custom("Tab 1".to_owned()), Tab::Inspector]);
+        let [_, _] = tree.split_below(
+            NodeIndex::root(),
+            0.5,
+            vec![Tab::Missing("Tab 2".to_owned())],
+        );
+        let tree = repair_layout(tree, &tab_registry).unwrap();
+        let tabs: Vec<_> = tree
+            .iter()
+            .filter_map(|node| node.tabs())
+            .flatten()
+            .cloned()
+            .collect();
+        // the unregistered tab is kept as a placeholder, the registered one comes back, and the
+        // layout gets a viewport since it didn't have one
+        assert!(
+            tabs == [
+                Tab::Missing("Tab 1".to_owned()),
+                Tab::Inspector,
+                Tab::Viewport(ViewportId(0)),
+                Tab::Custom("Tab 2".to_owned()),
+            ]
+        );
+
+        assert!(repair_layout(Tree::new(Vec::new()), &tab_registry).is_none());
+    }
+
+    #[test]
+    fn migrating_layouts() {
+        // layouts used to be saved as just the tree
+        let tree = nested_layout();
+        let layout = parse_layout(&ron::to_string(&tree).unwrap()).unwrap();
+        assert_eq!(layout.version, 0);
+        let migrated = migrate_layout(layout).unwrap();
+        assert_eq!(describe_layout(&migrated), describe_layout(&tree));
+
+        // and the oldest of those are from before the viewport tab had an id
+        let tree = default_layout();
+        let viewport = ron::to_string(&Tab::Viewport(ViewportId(0))).unwrap();
+        let saved = ron::to_string(&tree)
+            .unwrap()
+            .replace(&viewport, "Viewport");
+        assert!(ron::from_str::<Tree<Tab>>(&saved).is_err());
+        let layout = parse_layout(&saved).unwrap();
+        assert_eq!(layout.version, 0);
+        let migrated = migrate_layout(layout).unwrap();
+        assert_eq!(describe_layout(&migrated), describe_layout(&tree));
+        assert!(viewport_ids(&migrated).eq([ViewportId(0)]));
+
+        // a layout from a newer version of the app can't be loaded
+        let layout = SavedLayout {
+            version: LAYOUT_VERSION + 1,
+            tree: default_layout(),
+        };
+        assert!(migrate_layout(layout).is_none());
+    }
+
+    #[test]
+    fn picking_selects_the_scene_object() {
+        let mut world = World::new();
+        let object = world.spawn(SceneObject).id();
+        let child = world.spawn_empty().set_parent(object).id();
+        let mesh = world
+            .spawn(Handle::<Mesh>::default())
+            .set_parent(child)
+            .id();
+        let loose_mesh = world.spawn(Handle::<Mesh>::default()).id();
+
+        let mut state =
+            SystemState::<(Query<&Parent>, Query<(), With<SceneObject>>)>::new(&mut world);
+        let (parents, scene_objects) = state.get(&world);
+        assert_eq!(scene_object_root(mesh, &parents, &scene_objects), object);
+        assert_eq!(scene_object_root(object, &parents, &scene_objects), object);
+        assert_eq!(
+            scene_object_root(loose_mesh, &parents, &scene_objects),
+            loose_mesh
+        );
+    }
+
+    #[test]
+    fn viewport_sizes() {
+        assert_eq!(viewport_target_size(Vec2::ZERO, 1.), None);
+        // too small in either direction is too small
+        assert_eq!(viewport_target_size(Vec2::new(400., 7.), 1.), None);
+        assert_eq!(viewport_target_size(Vec2::new(7., 300.), 1.), None);
+        assert_eq!(
+            viewport_target_size(Vec2::new(400., 300.), 1.),
+            Some(UVec2::new(400, 300))
+        );
+        // 4.5 points at a scale of 2 is still big enough
+        assert_eq!(
+            viewport_target_size(Vec2::new(4.5, 4.5), 2.),
+            Some(UVec2::new(9, 9))
+        );
+        // 333 * 1.5 is 499.5, which rounds up rather than getting cut down to 499
+        assert_eq!(
+            viewport_target_size(Vec2::new(333., 201.), 1.5),
+            Some(UVec2::new(500, 302))
+        );
+    }
+}
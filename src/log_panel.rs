@@ -0,0 +1,164 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    prelude::*,
+    utils::tracing::{
+        self,
+        field::{Field, Visit},
+        Event, Level, Subscriber,
+    },
+};
+use bevy_egui::egui;
+use tracing_subscriber::{layer::Context, prelude::*, EnvFilter, Layer, Registry};
+
+// the log tab only remembers this many lines, dropping the oldest ones first
+const LOG_CAPACITY: usize = 1000;
+
+// sets up logging like bevy's LogPlugin does, but also keeps the most recent lines in memory so
+// that they can be shown in the log tab. it has to be used instead of the LogPlugin, since only one
+// logger can be set up per app
+pub struct LogPanelPlugin {
+    // the same as the LogPlugin's filter and level, and overridden by RUST_LOG in the same way
+    pub filter: String,
+    pub level: Level,
+}
+
+impl Default for LogPanelPlugin {
+    fn default() -> Self {
+        Self {
+            filter: "wgpu=error,naga=warn".to_owned(),
+            level: Level::INFO,
+        }
+    }
+}
+
+impl Plugin for LogPanelPlugin {
+    fn build(&self, app: &mut App) {
+        let buffer = LogBuffer::default();
+        app.insert_resource(buffer.clone());
+
+        // messages logged through the log crate (e.g. by wgpu) need forwarding on to tracing
+        let _ = tracing_log::LogTracer::init();
+        let default_filter = format!("{},{}", self.level, self.filter);
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(&default_filter))
+            .expect("Could not parse the log filter");
+        let subscriber = Registry::default()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .with(LogCaptureLayer { buffer });
+        if tracing::subscriber::set_global_default(subscriber).is_err() {
+            // print this directly, since whatever logger is set up may not be showing anything
+            eprintln!("Could not set up the log tab's logger, is bevy's LogPlugin still enabled?");
+        }
+    }
+}
+
+// one line of the log
+#[derive(Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+// the most recent log lines, shared between the tracing layer and the log tab
+#[derive(Resource, Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<LogLine>>>);
+
+impl LogBuffer {
+    fn push(&self, line: LogLine) {
+        let Ok(mut lines) = self.0.lock() else {
+            return;
+        };
+        if lines.len() >= LOG_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+struct LogCaptureLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogCaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        self.buffer.push(LogLine {
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_owned(),
+            message: message.0,
+        });
+    }
+}
+
+// turns the fields of an event into a single line, with the message first and any other fields
+// after it
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, "{value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+// the order the levels are shown in the filter dropdown, least to most verbose
+const LEVELS: [Level; 4] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+pub fn log_ui(ui: &mut egui::Ui, world: &mut World) {
+    let Some(buffer) = world.get_resource::<LogBuffer>() else {
+        ui.label("Add the LogPanelPlugin (in place of bevy's LogPlugin) to see the log here");
+        return;
+    };
+
+    // the filter is just ui state, so it lives in egui's memory
+    let filter_id = ui.id().with("log_filter");
+    let mut filter = ui.data(|data| data.get_temp::<Level>(filter_id).unwrap_or(Level::INFO));
+    egui::ComboBox::from_label("Level")
+        .selected_text(filter.as_str())
+        .show_ui(ui, |ui| {
+            for level in LEVELS {
+                ui.selectable_value(&mut filter, level, level.as_str());
+            }
+        });
+    ui.data_mut(|data| data.insert_temp(filter_id, filter));
+    ui.separator();
+
+    let Ok(lines) = buffer.0.lock() else {
+        return;
+    };
+    egui::ScrollArea::vertical()
+        .auto_shrink([false; 2])
+        // keep following new lines, unless the user has scrolled up to look at something
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            // more verbose levels compare as greater, so this keeps everything at least as severe
+            for line in lines.iter().filter(|line| line.level <= filter) {
+                let color = match line.level {
+                    Level::ERROR => egui::Color32::from_rgb(230, 80, 80),
+                    Level::WARN => egui::Color32::from_rgb(230, 190, 60),
+                    Level::INFO => ui.visuals().text_color(),
+                    _ => ui.visuals().weak_text_color(),
+                };
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{:>5} {}: {}",
+                        line.level, line.target, line.message
+                    ))
+                    .monospace()
+                    .color(color),
+                );
+            }
+        });
+}
@@ -0,0 +1,210 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureUsages},
+    },
+};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::{CameraPivot, RenderSettings, ViewportConfig, ViewportInput};
+
+// the minimap is only ever drawn small, so its image doesn't need resizing along with the tabs
+const MINIMAP_RESOLUTION: u32 = 256;
+// how far above the followed pivot the minimap camera sits
+const MINIMAP_HEIGHT: f32 = 100.;
+// half the width of the ground the minimap shows, in world units
+const MINIMAP_EXTENT: f32 = 10.;
+
+// which corner of the viewports the minimap is drawn in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MinimapCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl MinimapCorner {
+    pub const ALL: [MinimapCorner; 4] = [
+        MinimapCorner::TopLeft,
+        MinimapCorner::TopRight,
+        MinimapCorner::BottomLeft,
+        MinimapCorner::BottomRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MinimapCorner::TopLeft => "Top Left",
+            MinimapCorner::TopRight => "Top Right",
+            MinimapCorner::BottomLeft => "Bottom Left",
+            MinimapCorner::BottomRight => "Bottom Right",
+        }
+    }
+
+    fn align(self) -> egui::Align2 {
+        match self {
+            MinimapCorner::TopLeft => egui::Align2::LEFT_TOP,
+            MinimapCorner::TopRight => egui::Align2::RIGHT_TOP,
+            MinimapCorner::BottomLeft => egui::Align2::LEFT_BOTTOM,
+            MinimapCorner::BottomRight => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+}
+
+// the top down view drawn as an inset over the viewports
+#[derive(Resource)]
+pub struct MinimapSettings {
+    pub visible: bool,
+    pub corner: MinimapCorner,
+    // the width (and height) of the inset, in points
+    pub size: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            corner: MinimapCorner::BottomRight,
+            size: 160.,
+        }
+    }
+}
+
+// the camera and image behind the minimap. there's just the one, which follows whichever viewport
+// was used last
+#[derive(Resource)]
+pub struct Minimap {
+    texture_id: egui::TextureId,
+    camera: Entity,
+}
+
+// this runs after the viewport format has been checked, so the minimap's image can use it too
+pub fn setup_minimap(
+    mut commands: Commands,
+    config: Res<ViewportConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_user_textures: ResMut<EguiUserTextures>,
+) {
+    let size = Extent3d {
+        width: MINIMAP_RESOLUTION,
+        height: MINIMAP_RESOLUTION,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: config.format,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+    let texture_id = egui_user_textures.add_image(image.clone());
+
+    let camera = commands
+        .spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(image),
+                    // nothing is rendered until the minimap is turned on
+                    is_active: false,
+                    ..default()
+                },
+                projection: Projection::Orthographic(OrthographicProjection {
+                    scale: MINIMAP_EXTENT,
+                    scaling_mode: ScalingMode::FixedVertical(2.),
+                    ..default()
+                }),
+                // looking straight down, with -Z (forwards, for the default camera) at the top
+                transform: Transform::from_xyz(0., MINIMAP_HEIGHT, 0.)
+                    .looking_at(Vec3::ZERO, Vec3::NEG_Z),
+                ..default()
+            },
+            Name::new("Minimap Camera"),
+        ))
+        .id();
+    commands.insert_resource(Minimap { texture_id, camera });
+}
+
+// only render the minimap while it's shown, keeping it over the pivot of the last used viewport
+pub fn update_minimap(
+    settings: Res<MinimapSettings>,
+    minimap: Res<Minimap>,
+    viewport_input: Res<ViewportInput>,
+    render_settings: Res<RenderSettings>,
+    pivots: Query<&CameraPivot>,
+    mut cameras: Query<(&mut Camera, &mut Camera3d, &mut Transform)>,
+) {
+    let Ok((mut camera, mut camera_3d, mut transform)) = cameras.get_mut(minimap.camera) else {
+        return;
+    };
+    if camera.is_active != settings.visible {
+        camera.is_active = settings.visible;
+    }
+    if render_settings.is_changed() {
+        camera_3d.clear_color = ClearColorConfig::Custom(render_settings.clear_color);
+    }
+    let Some(pivot) = viewport_input
+        .camera
+        .and_then(|camera| pivots.get(camera).ok())
+    else {
+        return;
+    };
+    let translation = **pivot + Vec3::Y * MINIMAP_HEIGHT;
+    if transform.translation != translation {
+        transform.translation = translation;
+    }
+}
+
+// draw the minimap's image in its corner of a viewport's rect
+pub fn minimap_ui(ui: &mut egui::Ui, world: &World, rect: egui::Rect) {
+    let settings = world.resource::<MinimapSettings>();
+    if !settings.visible {
+        return;
+    }
+    let Some(minimap) = world.get_resource::<Minimap>() else {
+        return;
+    };
+    // keep it from covering most of a small viewport
+    let size = settings.size.min(rect.width().min(rect.height()) / 2.);
+    let inset = settings
+        .corner
+        .align()
+        .align_size_within_rect(egui::vec2(size, size), rect.shrink(6.));
+    ui.put(inset, egui::Image::new(minimap.texture_id, inset.size()));
+    ui.painter()
+        .rect_stroke(inset, 0., ui.visuals().widgets.noninteractive.bg_stroke);
+}
+
+pub fn minimap_settings_ui(ui: &mut egui::Ui, world: &mut World) {
+    // edit a copy, so the settings only get flagged as changed when they actually are
+    let settings = world.resource::<MinimapSettings>();
+    let (mut visible, mut corner) = (settings.visible, settings.corner);
+    ui.checkbox(&mut visible, "Show Minimap")
+        .on_hover_text("A top down view of the scene around the last used viewport's pivot");
+    ui.add_enabled_ui(visible, |ui| {
+        egui::ComboBox::from_label("Minimap Corner")
+            .selected_text(corner.name())
+            .show_ui(ui, |ui| {
+                for option in MinimapCorner::ALL {
+                    ui.selectable_value(&mut corner, option, option.name());
+                }
+            });
+    });
+    let settings = world.resource::<MinimapSettings>();
+    if visible != settings.visible || corner != settings.corner {
+        let mut settings = world.resource_mut::<MinimapSettings>();
+        settings.visible = visible;
+        settings.corner = corner;
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+// how long a notification stays up for, including fading out at the end, in seconds
+const NOTIFICATION_SECS: f64 = 4.;
+const FADE_SECS: f64 = 1.;
+// older notifications are dropped once there are more than this many on screen
+const MAX_NOTIFICATIONS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NotificationLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl NotificationLevel {
+    fn color(self) -> egui::Color32 {
+        match self {
+            NotificationLevel::Info => egui::Color32::from_rgb(220, 220, 220),
+            NotificationLevel::Warning => egui::Color32::from_rgb(230, 190, 60),
+            NotificationLevel::Error => egui::Color32::from_rgb(230, 80, 80),
+        }
+    }
+}
+
+struct Notification {
+    text: String,
+    level: NotificationLevel,
+    // when the notification was first shown, in seconds since startup. it's set by the ui rather
+    // than when it's pushed, so that pushing one doesn't need the time
+    shown_at: Option<f64>,
+}
+
+// messages shown briefly in the corner of the window, to let the user know how something went
+#[derive(Resource, Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: NotificationLevel, text: impl Into<String>) {
+        let text = text.into();
+        // something going wrong every frame shouldn't fill the screen with copies of the same thing
+        if self
+            .queue
+            .iter()
+            .any(|notification| notification.text == text)
+        {
+            return;
+        }
+        self.queue.push_back(Notification {
+            text,
+            level,
+            shown_at: None,
+        });
+        if self.queue.len() > MAX_NOTIFICATIONS {
+            self.queue.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(NotificationLevel::Info, text);
+    }
+
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.push(NotificationLevel::Warning, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(NotificationLevel::Error, text);
+    }
+}
+
+// draw the notifications over everything else in the bottom right corner, fading each one out at
+// the end of its time
+pub fn notifications_ui(ctx: &egui::Context, notifications: &mut Notifications, now: f64) {
+    notifications.queue.retain(|notification| {
+        notification
+            .shown_at
+            .map_or(true, |shown_at| now - shown_at < NOTIFICATION_SECS)
+    });
+    if notifications.queue.is_empty() {
+        return;
+    }
+
+    egui::Area::new("notifications")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10., -10.))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            for notification in &mut notifications.queue {
+                let shown_at = *notification.shown_at.get_or_insert(now);
+                let remaining = NOTIFICATION_SECS - (now - shown_at);
+                let opacity = (remaining / FADE_SECS).clamp(0., 1.) as f32;
+                egui::Frame::popup(ui.style())
+                    .fill(ui.visuals().window_fill().linear_multiply(opacity))
+                    .stroke(egui::Stroke::NONE)
+                    .shadow(egui::epaint::Shadow::NONE)
+                    .show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(&notification.text)
+                                .color(notification.level.color().linear_multiply(opacity)),
+                        );
+                    });
+            }
+        });
+    // keep redrawing until they've all faded, even if nothing else is happening
+    ctx.request_repaint();
+}
@@ -0,0 +1,164 @@
+use bevy::{pbr::NotShadowCaster, prelude::*, render::render_resource::Face};
+
+use crate::{Hovered, Selected, SelectionChanged};
+
+// marker struct for the outline drawn around the selected entity
+#[derive(Component)]
+pub struct SelectionOutline;
+
+// marker struct for the outline drawn around the entity under the pointer
+#[derive(Component)]
+pub struct HoverOutline;
+
+// how the outlines around the selected and hovered entities look
+#[derive(Resource)]
+pub struct OutlineSettings {
+    pub visible: bool,
+    pub color: Color,
+    // a fainter color, so it's clear which entity is actually selected
+    pub hover_color: Color,
+    // how much bigger than the entity the outline is, as a fraction of its size
+    pub width: f32,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            color: Color::rgb(1.0, 0.6, 0.1),
+            hover_color: Color::rgb(0.6, 0.75, 0.9),
+            width: 0.04,
+        }
+    }
+}
+
+// every outline of a kind shares the one material, so changing the color only means changing it
+// once
+#[derive(Resource)]
+pub struct OutlineMaterials {
+    selected: Handle<StandardMaterial>,
+    hovered: Handle<StandardMaterial>,
+}
+
+pub fn setup_outline(
+    mut commands: Commands,
+    settings: Res<OutlineSettings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(OutlineMaterials {
+        selected: materials.add(outline_material(settings.color)),
+        hovered: materials.add(outline_material(settings.hover_color)),
+    });
+}
+
+pub fn update_outline_materials(
+    settings: Res<OutlineSettings>,
+    outline_materials: Res<OutlineMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some(material) = materials.get_mut(&outline_materials.selected) {
+        *material = outline_material(settings.color);
+    }
+    if let Some(material) = materials.get_mut(&outline_materials.hovered) {
+        *material = outline_material(settings.hover_color);
+    }
+}
+
+// the outline is a slightly bigger copy of the selected mesh with only its back faces drawn, so it
+// peeks out around the edges of the original. it's a child of the selected entity so that it
+// follows it around
+pub fn update_outline(
+    mut commands: Commands,
+    selected: Res<Selected>,
+    mut selection_changed: EventReader<SelectionChanged>,
+    settings: Res<OutlineSettings>,
+    outline_materials: Res<OutlineMaterials>,
+    meshes: Query<Ref<Handle<Mesh>>>,
+    outlines: Query<Entity, With<SelectionOutline>>,
+) {
+    // the selected entity's mesh can be swapped for another from the inspector
+    let mesh_changed =
+        selected.is_some_and(|entity| meshes.get(entity).is_ok_and(|mesh| mesh.is_changed()));
+    // the events all need reading, so they aren't seen again next frame
+    let selection_changed = selection_changed.iter().count() > 0;
+    if !selection_changed && !settings.is_changed() && !mesh_changed {
+        return;
+    }
+    let target = (**selected).filter(|_| settings.visible);
+    rebuild_outline(
+        &mut commands,
+        target.and_then(|entity| Some((entity, meshes.get(entity).ok()?.clone()))),
+        &outlines,
+        outline_materials.selected.clone(),
+        settings.width,
+        SelectionOutline,
+    );
+}
+
+// the same as the selection outline, but for whatever's under the pointer. the selected entity is
+// left without one, so as not to cover up its own outline
+pub fn update_hover_outline(
+    mut commands: Commands,
+    hovered: Res<Hovered>,
+    selected: Res<Selected>,
+    settings: Res<OutlineSettings>,
+    outline_materials: Res<OutlineMaterials>,
+    meshes: Query<&Handle<Mesh>>,
+    outlines: Query<Entity, With<HoverOutline>>,
+) {
+    if !hovered.is_changed() && !selected.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let target = (**hovered).filter(|&entity| settings.visible && **selected != Some(entity));
+    rebuild_outline(
+        &mut commands,
+        target.and_then(|entity| Some((entity, meshes.get(entity).ok()?.clone()))),
+        &outlines,
+        outline_materials.hovered.clone(),
+        settings.width,
+        HoverOutline,
+    );
+}
+
+// get rid of the old outline, and give the target (and its mesh) a new one if there is a target
+fn rebuild_outline(
+    commands: &mut Commands,
+    target: Option<(Entity, Handle<Mesh>)>,
+    outlines: impl IntoIterator<Item = Entity>,
+    material: Handle<StandardMaterial>,
+    width: f32,
+    marker: impl Component,
+) {
+    for outline in outlines {
+        commands.entity(outline).despawn_recursive();
+    }
+    let Some((target, mesh)) = target else {
+        return;
+    };
+    let outline = commands
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_scale(Vec3::splat(1. + width)),
+                ..default()
+            },
+            marker,
+            NotShadowCaster,
+        ))
+        .id();
+    commands.entity(target).add_child(outline);
+}
+
+fn outline_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        unlit: true,
+        // draw the inside of the mesh, so the outline only shows around the edges
+        cull_mode: Some(Face::Front),
+        ..default()
+    }
+}
@@ -0,0 +1,784 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::{AssetPath, LoadState},
+    prelude::*,
+    render::primitives::Aabb,
+    window::FileDragAndDrop,
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{FrameScene, Notifications, Selected, ViewportRect};
+
+// marker struct for the things in the scene that are being edited, as opposed to the editor's own
+// entities like the viewport cameras and the grid
+#[derive(Component)]
+pub struct SceneObject;
+
+// marker struct for the main directional light, which can be pointed around from the lighting tab
+#[derive(Component)]
+pub struct KeyLight;
+
+// the meshes that can be added to the scene from the add menu. it's also kept on the entities
+// using them, so that saved scenes can say which mesh to make rather than storing the mesh itself,
+// and so the inspector can show which one an entity is
+#[derive(Component, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Primitive {
+    Cube,
+    Sphere,
+    Plane,
+    Cylinder,
+    Capsule,
+    Torus,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 6] = [
+        Primitive::Cube,
+        Primitive::Sphere,
+        Primitive::Plane,
+        Primitive::Cylinder,
+        Primitive::Capsule,
+        Primitive::Torus,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Cube => "Cube",
+            Primitive::Sphere => "Sphere",
+            Primitive::Plane => "Plane",
+            Primitive::Cylinder => "Cylinder",
+            Primitive::Capsule => "Capsule",
+            Primitive::Torus => "Torus",
+        }
+    }
+
+    // every primitive is about 1 unit across, and can be scaled with its transform
+    pub fn mesh(self) -> Mesh {
+        match self {
+            Primitive::Cube => Mesh::from(shape::Cube { size: 1.0 }),
+            Primitive::Sphere => Mesh::from(shape::UVSphere {
+                radius: 0.5,
+                ..default()
+            }),
+            Primitive::Plane => Mesh::from(shape::Plane::from_size(1.0)),
+            Primitive::Cylinder => Mesh::from(shape::Cylinder {
+                radius: 0.5,
+                height: 1.0,
+                ..default()
+            }),
+            Primitive::Capsule => Mesh::from(shape::Capsule {
+                radius: 0.5,
+                depth: 1.0,
+                ..default()
+            }),
+            Primitive::Torus => Mesh::from(shape::Torus {
+                radius: 0.35,
+                ring_radius: 0.15,
+                ..default()
+            }),
+        }
+    }
+}
+
+// add a new mesh to the scene at the origin, returning the entity so it can be selected
+pub fn spawn_primitive(world: &mut World, primitive: Primitive) -> Entity {
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(primitive.mesh());
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    world
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                ..default()
+            },
+            SceneObject,
+            primitive,
+            Name::new(primitive.name()),
+        ))
+        .id()
+}
+
+// how big the sphere drawn at each point light is, which is also how close to it a click has to be
+// to select it
+pub const LIGHT_ICON_RADIUS: f32 = 0.25;
+
+// a point light has no mesh to click on, so it's given a bounding box of its own for picking
+pub fn spawn_point_light(world: &mut World) -> Entity {
+    world
+        .spawn((
+            PointLightBundle::default(),
+            Aabb::from_min_max(
+                Vec3::splat(-LIGHT_ICON_RADIUS),
+                Vec3::splat(LIGHT_ICON_RADIUS),
+            ),
+            SceneObject,
+            Name::new("Point Light"),
+        ))
+        .id()
+}
+
+// the same goes for spot lights, which start off above the origin shining down on it
+pub fn spawn_spot_light(world: &mut World) -> Entity {
+    world
+        .spawn((
+            SpotLightBundle {
+                transform: Transform::from_xyz(0., 5., 0.).looking_at(Vec3::ZERO, Vec3::Z),
+                ..default()
+            },
+            Aabb::from_min_max(
+                Vec3::splat(-LIGHT_ICON_RADIUS),
+                Vec3::splat(LIGHT_ICON_RADIUS),
+            ),
+            SceneObject,
+            Name::new("Spot Light"),
+        ))
+        .id()
+}
+
+// give the entity a new mesh of another kind, leaving its transform and material alone
+pub fn set_primitive(world: &mut World, entity: Entity, primitive: Primitive) {
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(primitive.mesh());
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    entity.insert((mesh, primitive));
+    // bevy only works out the bounding box of meshes without one, so it needs removing for the new
+    // mesh to get its own
+    entity.remove::<Aabb>();
+}
+
+// what the editor is allowed to delete
+#[derive(Resource, Default)]
+pub struct SceneEditSettings {
+    // lights are protected by default, since deleting the only one leaves the scene in the dark
+    pub allow_deleting_lights: bool,
+}
+
+// despawn the entity and its children, unless it isn't part of the scene (e.g. a viewport camera)
+// or is a light that isn't allowed to be deleted. returns whether it was deleted
+pub fn delete(world: &mut World, entity: Entity) -> bool {
+    let allow_deleting_lights = world.resource::<SceneEditSettings>().allow_deleting_lights;
+    let Some(entity_ref) = world.get_entity(entity) else {
+        return false;
+    };
+    let is_light = entity_ref.contains::<DirectionalLight>()
+        || entity_ref.contains::<PointLight>()
+        || entity_ref.contains::<SpotLight>();
+    if !entity_ref.contains::<SceneObject>() || (is_light && !allow_deleting_lights) {
+        return false;
+    }
+    world.entity_mut(entity).despawn_recursive();
+    true
+}
+
+// how far a duplicate is moved from the original, so that it doesn't sit exactly on top of it
+const DUPLICATE_OFFSET: Vec3 = Vec3::new(1., 0., 0.);
+
+// spawn a copy of a mesh (or loaded gltf) in the scene, returning the copy. a mesh's material is
+// copied too rather than shared, so that editing the copy doesn't change the original as well
+pub fn duplicate(world: &mut World, entity: Entity) -> Option<Entity> {
+    let entity_ref = world.get_entity(entity)?;
+    if !entity_ref.contains::<SceneObject>() {
+        return None;
+    }
+    let mut transform = *entity_ref.get::<Transform>()?;
+    transform.translation += DUPLICATE_OFFSET;
+    let name = entity_ref
+        .get::<Name>()
+        .map_or_else(|| "Copy".to_owned(), |name| format!("{name} Copy"));
+
+    // a loaded gltf is copied by spawning its scene again, which shares its meshes and materials
+    if let Some(scene) = entity_ref.get::<Handle<Scene>>() {
+        let bundle = SceneBundle {
+            scene: scene.clone(),
+            transform,
+            ..default()
+        };
+        return Some(world.spawn((bundle, SceneObject, Name::new(name))).id());
+    }
+    let mesh = entity_ref.get::<Handle<Mesh>>()?.clone();
+    let primitive = entity_ref.get::<Primitive>().copied();
+    let material = entity_ref.get::<Handle<StandardMaterial>>()?.clone();
+
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    let material = materials.get(&material)?.clone();
+    let material = materials.add(material);
+    let mut copy = world.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform,
+            ..default()
+        },
+        SceneObject,
+        Name::new(name),
+    ));
+    if let Some(primitive) = primitive {
+        copy.insert(primitive);
+    }
+    Some(copy.id())
+}
+
+// where the scene gets saved to and opened from
+#[derive(Resource)]
+pub struct SceneFileSettings {
+    pub path: PathBuf,
+}
+
+impl Default for SceneFileSettings {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("scene.ron"),
+        }
+    }
+}
+
+// the scene as it's saved to a file. meshes are saved as the primitive they were made from and
+// gltf models as the file they were loaded from, so that everything can be made again from the
+// file alone
+#[derive(Serialize, Deserialize)]
+struct SavedScene {
+    objects: Vec<SavedObject>,
+    #[serde(default)]
+    models: Vec<SavedModel>,
+    #[serde(default)]
+    lights: Vec<SavedLight>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTransform {
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+}
+
+impl From<&Transform> for SavedTransform {
+    fn from(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.to_array(),
+        }
+    }
+}
+
+impl From<SavedTransform> for Transform {
+    fn from(saved: SavedTransform) -> Self {
+        Self {
+            translation: Vec3::from_array(saved.translation),
+            rotation: Quat::from_array(saved.rotation),
+            scale: Vec3::from_array(saved.scale),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedObject {
+    name: Option<String>,
+    primitive: Primitive,
+    transform: SavedTransform,
+    material: SavedMaterial,
+}
+
+// a gltf model, which gets loaded from its file again when the scene is opened
+#[derive(Serialize, Deserialize)]
+struct SavedModel {
+    name: Option<String>,
+    path: PathBuf,
+    transform: SavedTransform,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedLight {
+    name: Option<String>,
+    light: SavedLightKind,
+    transform: SavedTransform,
+    // whether it's the one the lighting tab points around
+    key_light: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SavedLightKind {
+    Directional {
+        color: [f32; 4],
+        illuminance: f32,
+        shadows: bool,
+    },
+    Point {
+        color: [f32; 4],
+        intensity: f32,
+        range: f32,
+        radius: f32,
+        shadows: bool,
+    },
+    Spot {
+        color: [f32; 4],
+        intensity: f32,
+        range: f32,
+        radius: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+        shadows: bool,
+    },
+}
+
+impl SavedLightKind {
+    fn new(
+        directional: Option<&DirectionalLight>,
+        point: Option<&PointLight>,
+        spot: Option<&SpotLight>,
+    ) -> Option<Self> {
+        if let Some(light) = directional {
+            return Some(SavedLightKind::Directional {
+                color: light.color.as_rgba_f32(),
+                illuminance: light.illuminance,
+                shadows: light.shadows_enabled,
+            });
+        }
+        if let Some(light) = point {
+            return Some(SavedLightKind::Point {
+                color: light.color.as_rgba_f32(),
+                intensity: light.intensity,
+                range: light.range,
+                radius: light.radius,
+                shadows: light.shadows_enabled,
+            });
+        }
+        let light = spot?;
+        Some(SavedLightKind::Spot {
+            color: light.color.as_rgba_f32(),
+            intensity: light.intensity,
+            range: light.range,
+            radius: light.radius,
+            inner_angle: light.inner_angle,
+            outer_angle: light.outer_angle,
+            shadows: light.shadows_enabled,
+        })
+    }
+}
+
+// the material properties that can be edited in the ui
+#[derive(Serialize, Deserialize)]
+struct SavedMaterial {
+    base_color: [f32; 4],
+    metallic: f32,
+    perceptual_roughness: f32,
+    reflectance: f32,
+    emissive: [f32; 4],
+}
+
+impl From<&StandardMaterial> for SavedMaterial {
+    fn from(material: &StandardMaterial) -> Self {
+        Self {
+            base_color: material.base_color.as_rgba_f32(),
+            metallic: material.metallic,
+            perceptual_roughness: material.perceptual_roughness,
+            reflectance: material.reflectance,
+            emissive: material.emissive.as_rgba_f32(),
+        }
+    }
+}
+
+impl From<SavedMaterial> for StandardMaterial {
+    fn from(saved: SavedMaterial) -> Self {
+        Self {
+            base_color: saved.base_color.into(),
+            metallic: saved.metallic,
+            perceptual_roughness: saved.perceptual_roughness,
+            reflectance: saved.reflectance,
+            emissive: saved.emissive.into(),
+            ..default()
+        }
+    }
+}
+
+// write the primitives, gltf models and lights in the scene to a ron file. anything else (like a
+// mesh the app made itself) can't be made again from a file, so it's left out with a warning
+pub fn save_scene(world: &mut World, path: &Path) {
+    let mut scene = SavedScene {
+        objects: Vec::new(),
+        models: Vec::new(),
+        lights: Vec::new(),
+    };
+    let mut skipped = Vec::new();
+    let mut query = world.query_filtered::<(
+        Entity,
+        Option<&Name>,
+        &Transform,
+        (Option<&Primitive>, Option<&Handle<StandardMaterial>>),
+        Option<&Handle<Scene>>,
+        (
+            Option<&DirectionalLight>,
+            Option<&PointLight>,
+            Option<&SpotLight>,
+        ),
+        Option<&KeyLight>,
+    ), With<SceneObject>>();
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    let asset_server = world.resource::<AssetServer>();
+    for (entity, name, transform, mesh, gltf, light, key_light) in query.iter(world) {
+        let name = name.map(|name| name.to_string());
+        if let (Some(&primitive), Some(material)) = mesh {
+            if let Some(material) = materials.get(material) {
+                scene.objects.push(SavedObject {
+                    name,
+                    primitive,
+                    transform: transform.into(),
+                    material: material.into(),
+                });
+                continue;
+            }
+        } else if let Some(gltf) = gltf {
+            if let Some(asset_path) = asset_server.get_handle_path(gltf) {
+                scene.models.push(SavedModel {
+                    name,
+                    path: asset_path.path().to_owned(),
+                    transform: transform.into(),
+                });
+                continue;
+            }
+        } else if let Some(light) = SavedLightKind::new(light.0, light.1, light.2) {
+            scene.lights.push(SavedLight {
+                name,
+                light,
+                transform: transform.into(),
+                key_light: key_light.is_some(),
+            });
+            continue;
+        }
+        skipped.push(name.unwrap_or_else(|| format!("{entity:?}")));
+    }
+    if !skipped.is_empty() {
+        warn!(
+            "Left {} out of the saved scene, since they can't be made again from a file",
+            skipped.join(", ")
+        );
+        world.resource_mut::<Notifications>().warning(format!(
+            "{} objects couldn't be saved, only primitives, gltf models and lights can be",
+            skipped.len()
+        ));
+    }
+
+    let result = ron::ser::to_string_pretty(&scene, default())
+        .map_err(|err| err.to_string())
+        .and_then(|scene| std::fs::write(path, scene).map_err(|err| err.to_string()));
+    let mut notifications = world.resource_mut::<Notifications>();
+    match result {
+        Ok(()) => {
+            info!("Saved the scene to {}", path.display());
+            notifications.info(format!("Saved the scene to {}", path.display()));
+        }
+        Err(err) => {
+            warn!("Could not save the scene to {}: {err}", path.display());
+            notifications.error(format!("Could not save the scene to {}", path.display()));
+        }
+    }
+}
+
+// replace everything in the scene with what's saved in a ron file. returns whether the scene was
+// opened
+pub fn open_scene(world: &mut World, path: &Path) -> bool {
+    let scene = std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|scene| ron::from_str::<SavedScene>(&scene).map_err(|err| err.to_string()));
+    let scene = match scene {
+        Ok(scene) => scene,
+        Err(err) => {
+            warn!("Could not open the scene from {}: {err}", path.display());
+            world
+                .resource_mut::<Notifications>()
+                .error(format!("Could not open the scene from {}", path.display()));
+            return false;
+        }
+    };
+
+    let existing: Vec<Entity> = world
+        .query_filtered::<Entity, With<SceneObject>>()
+        .iter(world)
+        .collect();
+    for entity in existing {
+        // it might have been the child of one that's already been despawned
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+    for object in scene.objects {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(object.primitive.mesh());
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(object.material.into());
+        let name = object
+            .name
+            .unwrap_or_else(|| object.primitive.name().to_owned());
+        world.spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform: object.transform.into(),
+                ..default()
+            },
+            SceneObject,
+            object.primitive,
+            Name::new(name),
+        ));
+    }
+    for model in scene.models {
+        let entity = load_scene(world, model.path);
+        let mut entity = world.entity_mut(entity);
+        entity.insert(Transform::from(model.transform));
+        if let Some(name) = model.name {
+            entity.insert(Name::new(name));
+        }
+    }
+    for saved in scene.lights {
+        let entity = match saved.light {
+            SavedLightKind::Directional {
+                color,
+                illuminance,
+                shadows,
+            } => world
+                .spawn((
+                    DirectionalLightBundle {
+                        directional_light: DirectionalLight {
+                            color: color.into(),
+                            illuminance,
+                            shadows_enabled: shadows,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    SceneObject,
+                    Name::new("Directional Light"),
+                ))
+                .id(),
+            SavedLightKind::Point {
+                color,
+                intensity,
+                range,
+                radius,
+                shadows,
+            } => {
+                let entity = spawn_point_light(world);
+                world.entity_mut(entity).insert(PointLight {
+                    color: color.into(),
+                    intensity,
+                    range,
+                    radius,
+                    shadows_enabled: shadows,
+                    ..default()
+                });
+                entity
+            }
+            SavedLightKind::Spot {
+                color,
+                intensity,
+                range,
+                radius,
+                inner_angle,
+                outer_angle,
+                shadows,
+            } => {
+                let entity = spawn_spot_light(world);
+                world.entity_mut(entity).insert(SpotLight {
+                    color: color.into(),
+                    intensity,
+                    range,
+                    radius,
+                    inner_angle,
+                    outer_angle,
+                    shadows_enabled: shadows,
+                    ..default()
+                });
+                entity
+            }
+        };
+        let mut entity = world.entity_mut(entity);
+        entity.insert(Transform::from(saved.transform));
+        if let Some(name) = saved.name {
+            entity.insert(Name::new(name));
+        }
+        if saved.key_light {
+            entity.insert(KeyLight);
+        }
+    }
+    **world.resource_mut::<FrameScene>() = true;
+    info!("Opened the scene from {}", path.display());
+    world
+        .resource_mut::<Notifications>()
+        .info(format!("Opened the scene from {}", path.display()));
+    true
+}
+
+// gltf scenes dropped onto the viewport that are still loading, so they can be cleared up again if
+// the load fails
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PendingSceneLoads(Vec<(Entity, Handle<Scene>, PathBuf)>);
+
+pub fn is_gltf(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+        })
+}
+
+// the first scene in a gltf file, named after the file
+fn gltf_scene(asset_server: &AssetServer, path: &Path) -> (SceneBundle, Name) {
+    let scene = asset_server.load(AssetPath::new(path.to_owned(), Some("Scene0".to_owned())));
+    let name = path.file_stem().map_or_else(
+        || "Scene".to_owned(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    (SceneBundle { scene, ..default() }, Name::new(name))
+}
+
+// add a gltf file's scene to the scene, the same as dropping it onto the viewport does
+pub fn load_scene(world: &mut World, path: PathBuf) -> Entity {
+    let (bundle, name) = gltf_scene(world.resource::<AssetServer>(), &path);
+    let scene = bundle.scene.clone();
+    let entity = world.spawn((bundle, SceneObject, name)).id();
+    world
+        .resource_mut::<PendingSceneLoads>()
+        .push((entity, scene, path));
+    entity
+}
+
+// load any gltf files dropped onto the viewport into the scene
+pub fn load_dropped_files(
+    mut commands: Commands,
+    mut drops: EventReader<FileDragAndDrop>,
+    windows: Query<&Window>,
+    viewport_rect: Res<ViewportRect>,
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingSceneLoads>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for drop in drops.iter() {
+        let FileDragAndDrop::DroppedFile { window, path_buf } = drop else {
+            continue;
+        };
+        // the cursor position isn't always kept up to date while a file is dragged over the
+        // window, so only ignore drops which definitely missed the viewport. the ones that didn't
+        // might be meant for somewhere else, like the inspector's texture slot
+        let cursor = windows.get(*window).ok().and_then(Window::cursor_position);
+        if cursor.is_some_and(|cursor| !viewport_rect.contains(cursor)) {
+            continue;
+        }
+        if !is_gltf(path_buf) {
+            warn!(
+                "Only gltf files can be loaded into the scene, not {}",
+                path_buf.display()
+            );
+            notifications.warning("Only gltf files can be loaded into the scene");
+            continue;
+        }
+
+        let (bundle, name) = gltf_scene(&asset_server, path_buf);
+        let scene = bundle.scene.clone();
+        let entity = commands.spawn((bundle, SceneObject, name)).id();
+        pending.push((entity, scene, path_buf.clone()));
+    }
+}
+
+pub fn check_scene_loads(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingSceneLoads>,
+    mut notifications: ResMut<Notifications>,
+    mut selected: ResMut<Selected>,
+    mut frame_scene: ResMut<FrameScene>,
+) {
+    pending.retain(
+        |(entity, scene, path)| match asset_server.get_load_state(scene) {
+            LoadState::Failed => {
+                warn!("Could not load {}", path.display());
+                notifications.error(format!("Could not load {}", path.display()));
+                // it might have been deleted while it was loading
+                if let Some(entity) = commands.get_entity(*entity) {
+                    entity.despawn_recursive();
+                }
+                false
+            }
+            // select it once there's something to see, since it's probably about to be moved
+            LoadState::Loaded => {
+                **selected = Some(*entity);
+                **frame_scene = true;
+                false
+            }
+            _ => true,
+        },
+    );
+}
+
+// where the inspector's texture slot is in the window (in logical pixels, the same as egui's points)
+// and which material it's for, so that images can be dropped onto it. it's only set while the slot
+// is being shown
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct TextureDropTarget(pub Option<(egui::Rect, Handle<StandardMaterial>)>);
+
+// textures that are still loading, along with the material each one is going on
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PendingTextureLoads(Vec<(Handle<StandardMaterial>, Handle<Image>, PathBuf)>);
+
+pub fn load_texture(world: &mut World, material: Handle<StandardMaterial>, path: PathBuf) {
+    let image = world.resource::<AssetServer>().load(path.clone());
+    world
+        .resource_mut::<PendingTextureLoads>()
+        .push((material, image, path));
+}
+
+// load any files dropped onto the texture slot as the material's texture
+pub fn load_dropped_textures(
+    mut drops: EventReader<FileDragAndDrop>,
+    windows: Query<&Window>,
+    drop_target: Res<TextureDropTarget>,
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingTextureLoads>,
+) {
+    for drop in drops.iter() {
+        let FileDragAndDrop::DroppedFile { window, path_buf } = drop else {
+            continue;
+        };
+        let Some((rect, material)) = &**drop_target else {
+            continue;
+        };
+        let cursor = windows.get(*window).ok().and_then(Window::cursor_position);
+        if !cursor.is_some_and(|cursor| rect.contains(egui::pos2(cursor.x, cursor.y))) {
+            continue;
+        }
+        let image = asset_server.load(path_buf.clone());
+        pending.push((material.clone(), image, path_buf.clone()));
+    }
+}
+
+// put each texture on its material once it's loaded, or let the user know if it couldn't be
+pub fn check_texture_loads(
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingTextureLoads>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    pending.retain(
+        |(material, image, path)| match asset_server.get_load_state(image) {
+            LoadState::Failed => {
+                warn!("Could not load the texture {}", path.display());
+                notifications.error(format!("Could not load the texture {}", path.display()));
+                false
+            }
+            LoadState::Loaded => {
+                // the material might have been removed while the texture was loading
+                if let Some(material) = materials.get_mut(material) {
+                    material.base_color_texture = Some(image.clone());
+                }
+                false
+            }
+            _ => true,
+        },
+    );
+}
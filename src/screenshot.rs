@@ -0,0 +1,332 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{
+            Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor,
+            Extent3d, ImageCopyBuffer, ImageDataLayout, Maintain, MapMode, TextureDimension,
+            TextureFormat,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+    },
+    tasks::AsyncComputeTaskPool,
+};
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::Notifications;
+
+// copies viewport images back from the gpu and saves them as pngs
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        // the render world sends finished screenshots back to the main world through this
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        app.init_resource::<ScreenshotSettings>()
+            .init_resource::<ScreenshotRequests>()
+            .init_resource::<LastScreenshot>()
+            .insert_resource(ScreenshotReceiver(receiver))
+            // requests only need to survive until they've been extracted
+            .add_systems(First, clear_screenshot_requests)
+            .add_systems(Update, save_screenshots);
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(ScreenshotSender(sender))
+            .init_resource::<ScreenshotRequests>()
+            .init_resource::<PendingReadbacks>()
+            .add_systems(ExtractSchedule, extract_screenshot_requests)
+            // the copy has to happen after the viewport cameras have rendered this frame
+            .add_systems(
+                Render,
+                (copy_screenshots, receive_readbacks)
+                    .chain()
+                    .after(RenderSet::Render)
+                    .before(RenderSet::Cleanup),
+            );
+    }
+}
+
+// where screenshots get saved to
+#[derive(Resource)]
+pub struct ScreenshotSettings {
+    pub directory: PathBuf,
+    // save each screenshot before carrying on with the frame, rather than in the background. it
+    // holds the frame up, but the file is there as soon as the screenshot is, for when the app is
+    // about to exit
+    pub wait_for_save: bool,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("screenshots"),
+            wait_for_save: false,
+        }
+    }
+}
+
+// the images that should be saved as screenshots this frame. the image has to have been created
+// with TextureUsages::COPY_SRC so that it can be copied out of
+#[derive(Resource, Deref, DerefMut, Default, Clone)]
+pub struct ScreenshotRequests(Vec<Handle<Image>>);
+
+// the most recent screenshot, for anything that wants its pixels rather than the saved file
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct LastScreenshot(pub Option<Image>);
+
+// a screenshot which has been copied back from the gpu, as tightly packed rgba
+struct Screenshot {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Resource, Deref)]
+struct ScreenshotReceiver(Receiver<Screenshot>);
+
+#[derive(Resource, Deref)]
+struct ScreenshotSender(Sender<Screenshot>);
+
+// a copy into a buffer which is waiting for the gpu to finish with it
+struct Readback {
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    // rows in the buffer have to be padded out to a multiple of 256 bytes
+    padded_bytes_per_row: u32,
+    mapped: Receiver<Result<(), BufferAsyncError>>,
+}
+
+#[derive(Resource, Default, Deref, DerefMut)]
+struct PendingReadbacks(Vec<Readback>);
+
+fn clear_screenshot_requests(mut requests: ResMut<ScreenshotRequests>) {
+    if !requests.is_empty() {
+        requests.clear();
+    }
+}
+
+fn extract_screenshot_requests(
+    mut requests: ResMut<ScreenshotRequests>,
+    main_requests: Extract<Res<ScreenshotRequests>>,
+) {
+    *requests = ScreenshotRequests::clone(&main_requests);
+}
+
+fn copy_screenshots(
+    requests: Res<ScreenshotRequests>,
+    images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    mut pending: ResMut<PendingReadbacks>,
+) {
+    for handle in requests.iter() {
+        let Some(gpu_image) = images.get(handle) else {
+            warn!("Could not take a screenshot, the viewport image isn't on the gpu yet");
+            continue;
+        };
+        let format = gpu_image.texture_format;
+        let Some(bytes_per_pixel) = bytes_per_pixel(format) else {
+            warn!("Could not take a screenshot, {format:?} viewport images aren't supported");
+            continue;
+        };
+        let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+        let padded_bytes_per_row =
+            RenderDevice::align_copy_bytes_per_row((width * bytes_per_pixel) as usize) as u32;
+
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("viewport_screenshot_buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("viewport_screenshot_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit([encoder.finish()]);
+
+        // don't wait around for the gpu, just check back on later frames
+        let (sender, mapped) = crossbeam_channel::bounded(1);
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        pending.push(Readback {
+            buffer,
+            width,
+            height,
+            format,
+            padded_bytes_per_row,
+            mapped,
+        });
+    }
+}
+
+fn receive_readbacks(
+    device: Res<RenderDevice>,
+    sender: Res<ScreenshotSender>,
+    mut pending: ResMut<PendingReadbacks>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    // give the gpu a chance to call the map callbacks, without blocking on it
+    device.wgpu_device().poll(Maintain::Poll);
+
+    pending.retain(|readback| match readback.mapped.try_recv() {
+        Ok(Ok(())) => {
+            let padded = readback.buffer.slice(..).get_mapped_range();
+            let mut data = Vec::with_capacity((readback.width * readback.height * 4) as usize);
+            // copy_screenshots has already checked that the format is supported
+            let bytes_per_pixel = bytes_per_pixel(readback.format).unwrap_or(4) as usize;
+            for row in padded.chunks(readback.padded_bytes_per_row as usize) {
+                for pixel in
+                    row[..readback.width as usize * bytes_per_pixel].chunks_exact(bytes_per_pixel)
+                {
+                    data.extend_from_slice(&to_rgba8(pixel, readback.format));
+                }
+            }
+            drop(padded);
+            readback.buffer.unmap();
+
+            let _ = sender.send(Screenshot {
+                data,
+                width: readback.width,
+                height: readback.height,
+            });
+            false
+        }
+        Ok(Err(err)) => {
+            warn!("Could not read back a screenshot from the gpu: {err}");
+            false
+        }
+        // not ready yet
+        Err(_) => true,
+    });
+}
+
+// the formats screenshots can be taken of, and how big each of their pixels is
+fn bytes_per_pixel(format: TextureFormat) -> Option<u32> {
+    match format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => Some(4),
+        TextureFormat::Rgba16Float => Some(8),
+        TextureFormat::Rgba32Float => Some(16),
+        _ => None,
+    }
+}
+
+// pngs are 8 bit srgb, so anything else needs converting. the float formats are linear, and
+// anything brighter than white gets clipped
+fn to_rgba8(pixel: &[u8], format: TextureFormat) -> [u8; 4] {
+    match format {
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            [pixel[2], pixel[1], pixel[0], pixel[3]]
+        }
+        TextureFormat::Rgba16Float => {
+            let channel =
+                |i: usize| f16_to_f32(u16::from_le_bytes([pixel[i * 2], pixel[i * 2 + 1]]));
+            Color::rgba_linear(channel(0), channel(1), channel(2), channel(3)).as_rgba_u8()
+        }
+        TextureFormat::Rgba32Float => {
+            let channel =
+                |i: usize| f32::from_le_bytes([0, 1, 2, 3].map(|byte| pixel[i * 4 + byte]));
+            Color::rgba_linear(channel(0), channel(1), channel(2), channel(3)).as_rgba_u8()
+        }
+        _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 == 0 { 1. } else { -1. };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32 / 1024.;
+    match exponent {
+        // subnormal
+        0 => sign * mantissa * 2f32.powi(-14),
+        31 if mantissa == 0. => sign * f32::INFINITY,
+        31 => f32::NAN,
+        _ => sign * (1. + mantissa) * 2f32.powi(exponent - 15),
+    }
+}
+
+// encode finished screenshots in the background, since doing it on the main thread would stall a
+// frame
+fn save_screenshots(
+    receiver: Res<ScreenshotReceiver>,
+    settings: Res<ScreenshotSettings>,
+    mut notifications: ResMut<Notifications>,
+    mut last_screenshot: ResMut<LastScreenshot>,
+) {
+    for screenshot in receiver.try_iter() {
+        **last_screenshot = Some(Image::new(
+            Extent3d {
+                width: screenshot.width,
+                height: screenshot.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            screenshot.data.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let directory = settings.directory.clone();
+        let path = directory.join(format!("viewport-{timestamp}.png"));
+        notifications.info(format!("Saving a screenshot to {}", path.display()));
+        if settings.wait_for_save {
+            save_png(&directory, &path, &screenshot);
+        } else {
+            AsyncComputeTaskPool::get()
+                .spawn(async move { save_png(&directory, &path, &screenshot) })
+                .detach();
+        }
+    }
+}
+
+fn save_png(directory: &Path, path: &Path, screenshot: &Screenshot) {
+    let result = std::fs::create_dir_all(directory)
+        .map_err(|err| err.to_string())
+        .and_then(|()| {
+            image::save_buffer(
+                path,
+                &screenshot.data,
+                screenshot.width,
+                screenshot.height,
+                image::ColorType::Rgba8,
+            )
+            .map_err(|err| err.to_string())
+        });
+    match result {
+        Ok(()) => info!("Saved a screenshot of the viewport to {}", path.display()),
+        Err(err) => warn!("Could not save a screenshot to {}: {err}", path.display()),
+    }
+}
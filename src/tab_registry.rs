@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+// draws the contents of a registered tab
+pub type TabUi = dyn Fn(&mut egui::Ui, &mut World) + Send + Sync;
+
+// the tabs added by the app itself, which can be opened from the window menu like any other
+#[derive(Resource, Default)]
+pub struct TabRegistry {
+    // kept in the order they were registered, so the window menu lists them in that order
+    tabs: Vec<(String, Box<TabUi>)>,
+}
+
+impl TabRegistry {
+    // registering a name again replaces the tab that was already there
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        ui: impl Fn(&mut egui::Ui, &mut World) + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        match self.tabs.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing_ui)) => *existing_ui = Box::new(ui),
+            None => self.tabs.push((name, Box::new(ui))),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TabUi> {
+        self.tabs
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, ui)| ui.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tabs.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+// lets apps add their own tabs with `app.register_viewport_tab("My Tab", |ui, world| { ... })`
+pub trait RegisterViewportTab {
+    fn register_viewport_tab(
+        &mut self,
+        name: impl Into<String>,
+        ui: impl Fn(&mut egui::Ui, &mut World) + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl RegisterViewportTab for App {
+    fn register_viewport_tab(
+        &mut self,
+        name: impl Into<String>,
+        ui: impl Fn(&mut egui::Ui, &mut World) + Send + Sync + 'static,
+    ) -> &mut Self {
+        // this can be called before or after the plugin is added
+        self.init_resource::<TabRegistry>();
+        self.world.resource_mut::<TabRegistry>().register(name, ui);
+        self
+    }
+}
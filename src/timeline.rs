@@ -0,0 +1,393 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::Selected;
+
+// keys closer together than this (in seconds) count as being at the same time, so adding a key
+// where there already is one replaces it
+const KEY_TOLERANCE: f32 = 0.01;
+// the space to the left of the tracks for the names of the properties, in points
+const LABEL_WIDTH: f32 = 80.;
+const ROW_HEIGHT: f32 = 18.;
+
+// the keyframes of one property, sorted by time
+pub struct Track<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: Copy> Track<T> {
+    pub fn keys(&self) -> &[(f32, T)] {
+        &self.keys
+    }
+
+    pub fn insert(&mut self, time: f32, value: T) {
+        let index = self
+            .keys
+            .iter()
+            .position(|&(key_time, _)| key_time >= time - KEY_TOLERANCE);
+        match index {
+            Some(index) if self.keys[index].0 <= time + KEY_TOLERANCE => {
+                self.keys[index] = (time, value);
+            }
+            Some(index) => self.keys.insert(index, (time, value)),
+            None => self.keys.push((time, value)),
+        }
+    }
+
+    // returns whether there was a key there to remove
+    pub fn remove(&mut self, time: f32) -> bool {
+        let len = self.keys.len();
+        self.keys
+            .retain(|&(key_time, _)| (key_time - time).abs() > KEY_TOLERANCE);
+        self.keys.len() != len
+    }
+
+    // the value at the given time, blending between the keys either side of it. before the first
+    // key and after the last one, they're held
+    fn sample(&self, time: f32, blend: impl Fn(T, T, f32) -> T) -> Option<T> {
+        match self.keys.iter().position(|&(key_time, _)| key_time > time) {
+            Some(0) => Some(self.keys[0].1),
+            Some(index) => {
+                let (start_time, start) = self.keys[index - 1];
+                let (end_time, end) = self.keys[index];
+                Some(blend(
+                    start,
+                    end,
+                    (time - start_time) / (end_time - start_time),
+                ))
+            }
+            None => self.keys.last().map(|&(_, value)| value),
+        }
+    }
+}
+
+// the keyframes of an entity's transform, with each part of it keyed separately
+#[derive(Default)]
+pub struct TransformClip {
+    pub translation: Track<Vec3>,
+    pub rotation: Track<Quat>,
+    pub scale: Track<Vec3>,
+}
+
+impl TransformClip {
+    // the transform at the given time. any part of it which hasn't been keyed is left as it is
+    pub fn sample(&self, time: f32, transform: &Transform) -> Transform {
+        Transform {
+            translation: self
+                .translation
+                .sample(time, Vec3::lerp)
+                .unwrap_or(transform.translation),
+            rotation: self
+                .rotation
+                .sample(time, Quat::slerp)
+                .unwrap_or(transform.rotation),
+            scale: self
+                .scale
+                .sample(time, Vec3::lerp)
+                .unwrap_or(transform.scale),
+        }
+    }
+
+    pub fn insert(&mut self, time: f32, transform: &Transform) {
+        self.translation.insert(time, transform.translation);
+        self.rotation.insert(time, transform.rotation);
+        self.scale.insert(time, transform.scale);
+    }
+
+    pub fn remove(&mut self, time: f32) -> bool {
+        // not short circuiting, so every track loses its key
+        self.translation.remove(time) | self.rotation.remove(time) | self.scale.remove(time)
+    }
+}
+
+// the clip being edited in the timeline tab, and how far through playing it is
+#[derive(Resource)]
+pub struct Timeline {
+    // the entity being animated. there's only the one for now
+    pub entity: Option<Entity>,
+    pub clip: TransformClip,
+    // where the playhead is, in seconds from the start
+    pub time: f32,
+    pub length: f32,
+    pub playing: bool,
+    pub looping: bool,
+    // while recording, any change to the entity's transform gets keyed at the playhead
+    pub recording: bool,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            entity: None,
+            clip: TransformClip::default(),
+            time: 0.,
+            length: 5.,
+            playing: false,
+            looping: true,
+            recording: false,
+        }
+    }
+}
+
+pub fn play_timeline(time: Res<Time>, mut timeline: ResMut<Timeline>) {
+    if !timeline.playing {
+        return;
+    }
+    let mut playhead = timeline.time + time.delta_seconds();
+    if playhead > timeline.length {
+        if timeline.looping {
+            playhead %= timeline.length;
+        } else {
+            playhead = timeline.length;
+            timeline.playing = false;
+        }
+    }
+    timeline.time = playhead;
+}
+
+// put the entity where the clip says it should be whenever the playhead moves or the keys change,
+// so that scrubbing while paused shows the animation too
+pub fn apply_timeline(timeline: Res<Timeline>, mut transforms: Query<&mut Transform>) {
+    if !timeline.is_changed() {
+        return;
+    }
+    let Some(Ok(mut transform)) = timeline.entity.map(|entity| transforms.get_mut(entity)) else {
+        return;
+    };
+    let sampled = timeline.clip.sample(timeline.time, &transform);
+    if *transform != sampled {
+        *transform = sampled;
+    }
+}
+
+// while recording, key whichever parts of the entity's transform get changed, by the inspector,
+// the gizmo or anything else. the changes apply_timeline makes already match the clip, so they
+// don't get keyed again
+pub fn record_keyframes(
+    mut timeline: ResMut<Timeline>,
+    transforms: Query<Ref<Transform>>,
+    mut previous: Local<Option<(Entity, Transform)>>,
+) {
+    let Some(Ok((entity, transform))) = timeline
+        .entity
+        .map(|entity| transforms.get(entity).map(|transform| (entity, transform)))
+    else {
+        *previous = None;
+        return;
+    };
+    let last = previous
+        .replace((entity, *transform))
+        .filter(|(last_entity, _)| *last_entity == entity)
+        .map(|(_, last)| last);
+    let Some(last) = last else {
+        return;
+    };
+    if !timeline.recording || timeline.playing || !transform.is_changed() {
+        return;
+    }
+
+    let time = timeline.time;
+    let clip = &timeline.clip;
+    let key_translation = transform.translation != last.translation
+        && clip.translation.sample(time, Vec3::lerp) != Some(transform.translation);
+    let key_rotation = transform.rotation != last.rotation
+        && clip.rotation.sample(time, Quat::slerp) != Some(transform.rotation);
+    let key_scale = transform.scale != last.scale
+        && clip.scale.sample(time, Vec3::lerp) != Some(transform.scale);
+    if key_translation {
+        timeline
+            .clip
+            .translation
+            .insert(time, transform.translation);
+    }
+    if key_rotation {
+        timeline.clip.rotation.insert(time, transform.rotation);
+    }
+    if key_scale {
+        timeline.clip.scale.insert(time, transform.scale);
+    }
+}
+
+pub fn timeline_ui(ui: &mut egui::Ui, world: &mut World) {
+    let selected = **world.resource::<Selected>();
+    let timeline = world.resource::<Timeline>();
+    // edit copies, so the timeline is only flagged as changed (which puts the entity back where the
+    // clip has it) when it actually is
+    let mut time = timeline.time;
+    let mut length = timeline.length;
+    let (mut playing, mut looping, mut recording) =
+        (timeline.playing, timeline.looping, timeline.recording);
+    let entity = timeline.entity;
+    let entity_name = entity.map(|entity| {
+        world
+            .get::<Name>(entity)
+            .map_or_else(|| format!("{entity:?}"), |name| name.to_string())
+    });
+
+    let mut animate_selection = false;
+    ui.horizontal(|ui| {
+        ui.label(match &entity_name {
+            Some(name) => format!("Animating {name}"),
+            None => "Not animating anything".to_owned(),
+        });
+        animate_selection = ui
+            .add_enabled(
+                selected.is_some() && selected != entity,
+                egui::Button::new("Animate Selection"),
+            )
+            .on_hover_text("Start a new clip for the selected entity, replacing this one")
+            .clicked();
+    });
+    let (mut add_key, mut delete_key) = (false, false);
+    ui.horizontal(|ui| {
+        if ui.button(if playing { "Pause" } else { "Play" }).clicked() {
+            // playing from the end starts again from the beginning
+            if !playing && time >= length {
+                time = 0.;
+            }
+            playing = !playing;
+        }
+        if ui.button("Stop").clicked() {
+            playing = false;
+            time = 0.;
+        }
+        ui.toggle_value(&mut looping, "Loop");
+        ui.toggle_value(&mut recording, "Record")
+            .on_hover_text("Key the entity's transform at the playhead whenever it's edited");
+        ui.separator();
+        ui.add_enabled_ui(entity.is_some(), |ui| {
+            add_key = ui
+                .button("Add Key")
+                .on_hover_text("Key the whole transform at the playhead")
+                .clicked();
+            delete_key = ui
+                .button("Delete Key")
+                .on_hover_text("Remove the keys at the playhead")
+                .clicked();
+        });
+        ui.separator();
+        ui.add(
+            egui::DragValue::new(&mut length)
+                .speed(0.1)
+                .clamp_range(0.1..=600.)
+                .suffix(" s"),
+        )
+        .on_hover_text("How long the clip is");
+    });
+    ui.label(format!("{time:.2} / {length:.2} s"));
+    if let Some(scrubbed) = tracks_ui(ui, &world.resource::<Timeline>().clip, time, length) {
+        time = scrubbed;
+    }
+    time = time.min(length);
+
+    if animate_selection {
+        let mut timeline = world.resource_mut::<Timeline>();
+        timeline.entity = selected;
+        timeline.clip = TransformClip::default();
+        time = 0.;
+        playing = false;
+    }
+    let timeline = world.resource::<Timeline>();
+    if time != timeline.time
+        || length != timeline.length
+        || playing != timeline.playing
+        || looping != timeline.looping
+        || recording != timeline.recording
+    {
+        let mut timeline = world.resource_mut::<Timeline>();
+        timeline.time = time;
+        timeline.length = length;
+        timeline.playing = playing;
+        timeline.looping = looping;
+        timeline.recording = recording;
+    }
+    let transform = entity.and_then(|entity| world.get::<Transform>(entity).copied());
+    if let (true, Some(transform)) = (add_key, transform) {
+        world
+            .resource_mut::<Timeline>()
+            .clip
+            .insert(time, &transform);
+    }
+    if delete_key {
+        world.resource_mut::<Timeline>().clip.remove(time);
+    }
+}
+
+// a ruler with the playhead on it, and a row of markers for each property's keys. clicking or
+// dragging anywhere on it moves the playhead there, which is returned
+fn tracks_ui(ui: &mut egui::Ui, clip: &TransformClip, time: f32, length: f32) -> Option<f32> {
+    let rows: [(&str, Vec<f32>); 3] = [
+        ("Translation", key_times(&clip.translation)),
+        ("Rotation", key_times(&clip.rotation)),
+        ("Scale", key_times(&clip.scale)),
+    ];
+    let size = egui::vec2(ui.available_width(), ROW_HEIGHT * (rows.len() + 1) as f32);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let stroke = visuals.widgets.noninteractive.bg_stroke;
+    let track_left = rect.left() + LABEL_WIDTH;
+    let track_width = (rect.right() - track_left).max(1.);
+    let x = |time: f32| track_left + time / length * track_width;
+
+    painter.rect_filled(rect, 2., visuals.extreme_bg_color);
+    // a tick for every second along the top
+    for second in 0..=length as u32 {
+        let x = x(second as f32);
+        painter.line_segment(
+            [
+                egui::pos2(x, rect.top()),
+                egui::pos2(x, rect.top() + ROW_HEIGHT),
+            ],
+            stroke,
+        );
+        painter.text(
+            egui::pos2(x + 2., rect.top()),
+            egui::Align2::LEFT_TOP,
+            second.to_string(),
+            egui::FontId::proportional(10.),
+            visuals.weak_text_color(),
+        );
+    }
+    for (row, (name, times)) in rows.iter().enumerate() {
+        let y = rect.top() + ROW_HEIGHT * (row as f32 + 1.5);
+        painter.text(
+            egui::pos2(rect.left() + 4., y),
+            egui::Align2::LEFT_CENTER,
+            name,
+            egui::FontId::proportional(12.),
+            visuals.text_color(),
+        );
+        painter.line_segment(
+            [egui::pos2(track_left, y), egui::pos2(rect.right(), y)],
+            stroke,
+        );
+        for &time in times {
+            painter.circle_filled(egui::pos2(x(time), y), 4., visuals.selection.bg_fill);
+        }
+    }
+    let playhead = x(time);
+    painter.line_segment(
+        [
+            egui::pos2(playhead, rect.top()),
+            egui::pos2(playhead, rect.bottom()),
+        ],
+        egui::Stroke::new(2., egui::Color32::from_rgb(230, 80, 80)),
+    );
+
+    if !response.clicked() && !response.dragged() {
+        return None;
+    }
+    let pointer = response.interact_pointer_pos()?;
+    Some(((pointer.x - track_left) / track_width * length).clamp(0., length))
+}
+
+fn key_times<T: Copy>(track: &Track<T>) -> Vec<f32> {
+    track.keys().iter().map(|&(time, _)| time).collect()
+}
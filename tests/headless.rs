@@ -0,0 +1,55 @@
+// these need a gpu (or a software adapter) to render with, like the --headless ci run does
+use bevy::prelude::*;
+use docking_viewport::{Primitive, SceneObject, ScreenshotSettings};
+
+// the grey the viewports are cleared to, as srgb bytes
+const CLEAR_COLOR: [u8; 4] = [102, 102, 102, 255];
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // unlit, so the cube comes out red whatever the lighting
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Primitive::Cube.mesh()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::RED,
+                unlit: true,
+                ..default()
+            }),
+            transform: Transform::from_scale(Vec3::splat(4.)),
+            ..default()
+        },
+        SceneObject,
+        Primitive::Cube,
+    ));
+}
+
+#[test]
+fn renders_the_cube() {
+    let directory = std::env::temp_dir().join("docking-viewport-headless-test");
+    let _ = std::fs::remove_dir_all(&directory);
+    let image = docking_viewport::render_frames(10, |app| {
+        app.world.resource_mut::<ScreenshotSettings>().directory = directory.clone();
+        app.add_systems(Startup, setup_scene);
+    })
+    .expect("the viewport's image should have been read back");
+
+    // the cameras start off looking at the origin, so the cube fills the middle of the image
+    let size = image.texture_descriptor.size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    let middle = ((height / 2) * width + width / 2) * 4;
+    let pixel: [u8; 4] = image.data[middle..middle + 4].try_into().unwrap();
+    assert_ne!(pixel, CLEAR_COLOR);
+    // tonemapping shifts the red a little, but it should still be mostly red
+    assert!(pixel[0] > pixel[1] && pixel[0] > pixel[2], "{pixel:?}");
+
+    // the png is written before render_frames returns, so it's there even if the app exits
+    let saved = std::fs::read_dir(&directory)
+        .expect("the screenshot directory should have been made")
+        .count();
+    assert_eq!(saved, 1);
+    let _ = std::fs::remove_dir_all(&directory);
+}